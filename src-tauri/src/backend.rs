@@ -0,0 +1,743 @@
+use screenshots::image::{self, RgbaImage};
+use screenshots::Screen;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::capture::{Rect, ScreenInfo};
+
+// Source of screen geometry and pixels for the capture and overlay commands.
+// Selected at startup with PRISM_CAPTURE_BACKEND=screenshots|x11|fake.
+pub trait CaptureBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn screens(&self) -> Result<Vec<ScreenInfo>, String>;
+
+    // Full-screen capture in physical pixels
+    fn capture_screen(&self, screen_id: u32) -> Result<RgbaImage, String>;
+
+    // Mouse pointer in logical desktop coordinates, when the platform exposes it
+    fn cursor_position(&self) -> Option<(f64, f64)>;
+
+    // Whether the primary mouse button is held, when the platform exposes it
+    fn primary_button_down(&self) -> Option<bool> {
+        None
+    }
+
+    // Contents of one window regardless of what covers it, keyed by
+    // FocusedWindowInfo.native_id
+    fn capture_window(&self, _window_id: u32) -> Result<WindowCapture, String> {
+        Err(format!("The {} capture backend cannot capture single windows", self.name()))
+    }
+
+    // Native id of the window with keyboard focus, when it isn't one of Prism's.
+    // macOS windows are picked from a list and matched by process and frame.
+    #[cfg_attr(target_os = "macos", allow(dead_code))]
+    fn active_window(&self) -> Option<u32> {
+        None
+    }
+}
+
+pub struct WindowCapture {
+    // Window contents in physical pixels
+    pub image: RgbaImage,
+    // Window bounds in logical desktop coordinates
+    pub frame: Rect,
+}
+
+pub fn from_env() -> Box<dyn CaptureBackend> {
+    let requested = std::env::var("PRISM_CAPTURE_BACKEND").unwrap_or_default();
+
+    match requested.as_str() {
+        "fake" => {
+            let dir = std::env::var("PRISM_FAKE_CAPTURE_DIR").unwrap_or_else(|_| ".".to_string());
+            match FakeBackend::from_dir(&dir) {
+                Ok(backend) => return Box::new(backend),
+                Err(e) => println!("[Prism] Fake capture backend unavailable ({}), using default", e),
+            }
+        }
+        #[cfg(target_os = "linux")]
+        "x11" => return Box::new(x11::X11Backend),
+        _ => {}
+    }
+
+    // Plain X11 sessions go through xcb directly; Wayland and other platforms use the screenshots crate
+    #[cfg(target_os = "linux")]
+    if std::env::var_os("DISPLAY").is_some() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        return Box::new(x11::X11Backend);
+    }
+
+    Box::new(ScreenshotsBackend)
+}
+
+// Default backend built on the screenshots crate
+pub struct ScreenshotsBackend;
+
+impl CaptureBackend for ScreenshotsBackend {
+    fn name(&self) -> &'static str {
+        "screenshots"
+    }
+
+    fn screens(&self) -> Result<Vec<ScreenInfo>, String> {
+        let screens = Screen::all().map_err(|e| e.to_string())?;
+        Ok(screens.iter().map(ScreenInfo::from).collect())
+    }
+
+    fn capture_screen(&self, screen_id: u32) -> Result<RgbaImage, String> {
+        let screens = Screen::all().map_err(|e| e.to_string())?;
+        let screen = screens
+            .iter()
+            .find(|s| s.display_info.id == screen_id)
+            .ok_or_else(|| format!("Screen {} not found", screen_id))?;
+
+        screen.capture().map_err(|e| e.to_string())
+    }
+
+    #[cfg(target_os = "macos")]
+    fn cursor_position(&self) -> Option<(f64, f64)> {
+        use core_graphics::event::CGEvent;
+        use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
+        // CGEvent locations are already in global display points
+        let source = CGEventSource::new(CGEventSourceStateID::CombinedSessionState).ok()?;
+        let location = CGEvent::new(source).ok()?.location();
+        Some((location.x, location.y))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn cursor_position(&self) -> Option<(f64, f64)> {
+        x11::query_pointer()
+    }
+
+    #[cfg(target_os = "macos")]
+    fn primary_button_down(&self) -> Option<bool> {
+        use core_graphics::event_source::CGEventSourceStateID;
+
+        // Not wrapped by the core-graphics crate
+        #[link(name = "CoreGraphics", kind = "framework")]
+        extern "C" {
+            fn CGEventSourceButtonState(state: CGEventSourceStateID, button: u32) -> bool;
+        }
+
+        // SAFETY: plain query with no pointers; button 0 is the left button
+        Some(unsafe { CGEventSourceButtonState(CGEventSourceStateID::CombinedSessionState, 0) })
+    }
+
+    #[cfg(target_os = "linux")]
+    fn primary_button_down(&self) -> Option<bool> {
+        x11::primary_button_down()
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    fn cursor_position(&self) -> Option<(f64, f64)> {
+        None
+    }
+
+    // XWayland and plain X11 windows can still be read through Composite
+    #[cfg(target_os = "linux")]
+    fn capture_window(&self, window_id: u32) -> Result<WindowCapture, String> {
+        x11::capture_window(window_id)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn active_window(&self) -> Option<u32> {
+        x11::active_window()
+    }
+
+    #[cfg(target_os = "macos")]
+    fn capture_window(&self, window_id: u32) -> Result<WindowCapture, String> {
+        macos::capture_window(window_id)
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub mod macos {
+    use super::*;
+    use core_foundation::base::{CFType, TCFType};
+    use core_foundation::dictionary::CFDictionary;
+    use core_foundation::number::CFNumber;
+    use core_foundation::string::{CFString, CFStringRef};
+    use core_graphics::geometry::{CGPoint, CGRect, CGSize};
+    use core_graphics::window::{self as cg, CGWindowID, CGWindowListOption};
+
+    fn number(entry: &CFDictionary<CFString, CFType>, key: CFStringRef) -> Option<i64> {
+        // SAFETY: the window info keys are static CFStrings owned by CoreGraphics
+        let key = unsafe { CFString::wrap_under_get_rule(key) };
+        entry.find(&key)?.downcast::<CFNumber>()?.to_i64()
+    }
+
+    // Window number, owner process and bounds (desktop points) of each window
+    fn windows(option: CGWindowListOption, relative_to: CGWindowID) -> Vec<(u32, i32, Rect)> {
+        let Some(descriptions) = cg::create_window_list(option, relative_to).and_then(cg::create_description_from_array)
+        else {
+            return Vec::new();
+        };
+        // SAFETY: as above
+        let bounds_key = unsafe { CFString::wrap_under_get_rule(cg::kCGWindowBounds) };
+
+        descriptions
+            .iter()
+            .filter_map(|entry| {
+                let id = number(&entry, unsafe { cg::kCGWindowNumber })?;
+                let pid = number(&entry, unsafe { cg::kCGWindowOwnerPID })?;
+                let bounds = CGRect::from_dict_representation(&entry.find(&bounds_key)?.downcast::<CFDictionary>()?)?;
+                let frame = Rect {
+                    x: bounds.origin.x,
+                    y: bounds.origin.y,
+                    width: bounds.size.width,
+                    height: bounds.size.height,
+                };
+                Some((id as u32, pid as i32, frame))
+            })
+            .collect()
+    }
+
+    // CGWindowID of a window System Events reported: the on-screen window of
+    // that process with the same bounds
+    pub fn window_number(process_id: i32, frame: &Rect) -> Option<u32> {
+        let option = cg::kCGWindowListOptionOnScreenOnly | cg::kCGWindowListExcludeDesktopElements;
+        let close = |a: f64, b: f64| (a - b).abs() <= 1.0;
+        windows(option, cg::kCGNullWindowID)
+            .into_iter()
+            .find(|(_, pid, bounds)| {
+                *pid == process_id
+                    && close(bounds.x, frame.x)
+                    && close(bounds.y, frame.y)
+                    && close(bounds.width, frame.width)
+                    && close(bounds.height, frame.height)
+            })
+            .map(|(id, _, _)| id)
+    }
+
+    // Read one window's backing store, so windows stacked on top of it are not
+    // in the image
+    pub fn capture_window(window_id: u32) -> Result<WindowCapture, String> {
+        let frame = windows(cg::kCGWindowListOptionIncludingWindow, window_id)
+            .into_iter()
+            .find(|(id, _, _)| *id == window_id)
+            .map(|(_, _, frame)| frame)
+            .ok_or_else(|| format!("Window {} not found", window_id))?;
+
+        let bounds = CGRect::new(&CGPoint::new(frame.x, frame.y), &CGSize::new(frame.width, frame.height));
+        let image = cg::create_image(
+            bounds,
+            cg::kCGWindowListOptionIncludingWindow,
+            window_id,
+            cg::kCGWindowImageBoundsIgnoreFraming | cg::kCGWindowImageBestResolution,
+        )
+        .ok_or_else(|| format!("Window {} could not be captured", window_id))?;
+        if image.bits_per_pixel() != 32 {
+            return Err(format!("Unsupported window image depth {}", image.bits_per_pixel()));
+        }
+
+        // BGRA rows, possibly padded
+        let (width, height, stride) = (image.width(), image.height(), image.bytes_per_row());
+        let data = image.data();
+        let bytes = data.bytes();
+        let mut rgba = Vec::with_capacity(width * height * 4);
+        for row in bytes.chunks(stride).take(height) {
+            for bgra in row[..width * 4].chunks_exact(4) {
+                rgba.extend_from_slice(&[bgra[2], bgra[1], bgra[0], bgra[3]]);
+            }
+        }
+
+        let image = RgbaImage::from_raw(width as u32, height as u32, rgba)
+            .ok_or_else(|| "Failed to build image from window data".to_string())?;
+        Ok(WindowCapture { image, frame })
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub mod x11 {
+    use super::*;
+    use std::sync::Arc;
+    use xcb::{composite, randr, x, Xid, XidNew};
+
+    // Captures the X root window with GetImage; monitors come from RandR.
+    // Works on any X server including Xvfb.
+    pub struct X11Backend;
+
+    struct Display {
+        conn: xcb::Connection,
+        screen_num: i32,
+    }
+
+    // One connection shared by every request. The cursor stream and click
+    // listener poll many times a second, too often to reconnect each time.
+    static DISPLAY: Mutex<Option<Arc<Display>>> = Mutex::new(None);
+
+    // The shared connection, reopened if the server dropped it
+    fn connect() -> Result<Arc<Display>, String> {
+        let mut display = DISPLAY.lock().unwrap();
+        if let Some(current) = display.as_ref().filter(|d| d.conn.has_error().is_ok()) {
+            return Ok(current.clone());
+        }
+
+        let optional = [xcb::Extension::RandR, xcb::Extension::Composite];
+        let (conn, screen_num) = xcb::Connection::connect_with_extensions(None, &[], &optional)
+            .map_err(|e| format!("Failed to connect to X server: {}", e))?;
+        let current = Arc::new(Display { conn, screen_num });
+        *display = Some(current.clone());
+        Ok(current)
+    }
+
+    fn root_screen(conn: &xcb::Connection, screen_num: i32) -> Result<&x::Screen, String> {
+        conn.get_setup()
+            .roots()
+            .nth(screen_num as usize)
+            .ok_or_else(|| "X screen not found".to_string())
+    }
+
+    impl CaptureBackend for X11Backend {
+        fn name(&self) -> &'static str {
+            "x11"
+        }
+
+        fn screens(&self) -> Result<Vec<ScreenInfo>, String> {
+            let display = connect()?;
+            let conn = &display.conn;
+            let root = root_screen(conn, display.screen_num)?;
+
+            let cookie = conn.send_request(&randr::GetMonitors {
+                window: root.root(),
+                get_active: true,
+            });
+
+            // Xvfb without RandR monitors still has a root window to capture
+            let monitors: Vec<ScreenInfo> = match conn.wait_for_reply(cookie) {
+                Ok(reply) => reply
+                    .monitors()
+                    .enumerate()
+                    .map(|(index, monitor)| ScreenInfo {
+                        id: index as u32,
+                        x: monitor.x() as i32,
+                        y: monitor.y() as i32,
+                        width: monitor.width() as u32,
+                        height: monitor.height() as u32,
+                        scale_factor: 1.0,
+                        is_primary: monitor.primary(),
+                    })
+                    .collect(),
+                Err(_) => Vec::new(),
+            };
+
+            if !monitors.is_empty() {
+                return Ok(monitors);
+            }
+
+            Ok(vec![ScreenInfo {
+                id: 0,
+                x: 0,
+                y: 0,
+                width: root.width_in_pixels() as u32,
+                height: root.height_in_pixels() as u32,
+                scale_factor: 1.0,
+                is_primary: true,
+            }])
+        }
+
+        fn capture_screen(&self, screen_id: u32) -> Result<RgbaImage, String> {
+            let screen = self
+                .screens()?
+                .into_iter()
+                .find(|s| s.id == screen_id)
+                .ok_or_else(|| format!("Screen {} not found", screen_id))?;
+
+            let display = connect()?;
+            let root = root_screen(&display.conn, display.screen_num)?;
+
+            capture_drawable(
+                &display.conn,
+                x::Drawable::Window(root.root()),
+                screen.x as i16,
+                screen.y as i16,
+                screen.width as u16,
+                screen.height as u16,
+            )
+        }
+
+        fn cursor_position(&self) -> Option<(f64, f64)> {
+            query_pointer()
+        }
+
+        fn primary_button_down(&self) -> Option<bool> {
+            primary_button_down()
+        }
+
+        fn capture_window(&self, window_id: u32) -> Result<WindowCapture, String> {
+            capture_window(window_id)
+        }
+
+        fn active_window(&self) -> Option<u32> {
+            active_window()
+        }
+    }
+
+    fn atom(conn: &xcb::Connection, name: &[u8]) -> Option<x::Atom> {
+        let cookie = conn.send_request(&x::InternAtom { only_if_exists: true, name });
+        Some(conn.wait_for_reply(cookie).ok()?.atom()).filter(|atom| *atom != x::ATOM_NONE)
+    }
+
+    fn cardinal(conn: &xcb::Connection, window: x::Window, property: x::Atom, r#type: x::Atom) -> Option<u32> {
+        let cookie = conn.send_request(&x::GetProperty {
+            delete: false,
+            window,
+            property,
+            r#type,
+            long_offset: 0,
+            long_length: 1,
+        });
+        conn.wait_for_reply(cookie).ok()?.value::<u32>().first().copied()
+    }
+
+    // The window manager's _NET_ACTIVE_WINDOW, unless it belongs to Prism
+    pub fn active_window() -> Option<u32> {
+        let display = connect().ok()?;
+        let conn = &display.conn;
+        let root = root_screen(conn, display.screen_num).ok()?.root();
+        let active = cardinal(conn, root, atom(conn, b"_NET_ACTIVE_WINDOW")?, x::ATOM_WINDOW).filter(|id| *id != 0)?;
+
+        // SAFETY: the id came from the window manager; a stale one just has no _NET_WM_PID
+        let window = unsafe { x::Window::new(active) };
+        let pid = atom(conn, b"_NET_WM_PID").and_then(|property| cardinal(conn, window, property, x::ATOM_CARDINAL));
+        if pid == Some(std::process::id()) {
+            return None;
+        }
+        Some(active)
+    }
+
+    // A compositing manager owns _NET_WM_CM_S<screen> and keeps every window
+    // redirected, so covered parts of a window still have current contents
+    fn compositor_running(conn: &xcb::Connection, screen_num: i32) -> bool {
+        let Some(selection) = atom(conn, format!("_NET_WM_CM_S{}", screen_num).as_bytes()) else {
+            return false;
+        };
+        conn.wait_for_reply(conn.send_request(&x::GetSelectionOwner { selection }))
+            .is_ok_and(|reply| !reply.owner().is_none())
+    }
+
+    // The window manager's frame around a client window (the child of the root
+    // it was reparented into), so the title bar is captured too
+    fn frame_window(conn: &xcb::Connection, mut window: x::Window, root: x::Window) -> Result<x::Window, String> {
+        loop {
+            let tree = conn
+                .wait_for_reply(conn.send_request(&x::QueryTree { window }))
+                .map_err(|e| format!("Window {} not found: {}", window.resource_id(), e))?;
+            if tree.parent() == root || tree.parent().is_none() {
+                return Ok(window);
+            }
+            window = tree.parent();
+        }
+    }
+
+    // Read a window's pixmap through the Composite extension, so windows
+    // stacked on top of it (Prism's panel, notifications) are not in the
+    // image. Only with a compositing manager: otherwise the covered parts have
+    // no contents to read, and the caller falls back to the screen.
+    pub fn capture_window(window_id: u32) -> Result<WindowCapture, String> {
+        let display = connect()?;
+        let conn = &display.conn;
+        if !conn.active_extensions().any(|extension| extension == xcb::Extension::Composite) {
+            return Err("X server does not support window capture".to_string());
+        }
+        if !compositor_running(conn, display.screen_num) {
+            return Err("No compositing manager is running, so covered windows can't be read".to_string());
+        }
+        let root = root_screen(conn, display.screen_num)?.root();
+        // SAFETY: any id is accepted by the server; unknown ones fail with BadWindow below
+        let window = frame_window(conn, unsafe { x::Window::new(window_id) }, root)?;
+
+        // NameWindowPixmap needs Composite 0.2
+        let version = conn
+            .wait_for_reply(conn.send_request(&composite::QueryVersion {
+                client_major_version: 0,
+                client_minor_version: 2,
+            }))
+            .map_err(|e| e.to_string())?;
+        if (version.major_version(), version.minor_version()) < (0, 2) {
+            return Err("X server Composite extension is older than 0.2".to_string());
+        }
+
+        let attributes = conn
+            .wait_for_reply(conn.send_request(&x::GetWindowAttributes { window }))
+            .map_err(|e| format!("Window {} not found: {}", window_id, e))?;
+        if attributes.map_state() != x::MapState::Viewable {
+            return Err(format!("Window {} is not visible", window_id));
+        }
+
+        let geometry = conn
+            .wait_for_reply(conn.send_request(&x::GetGeometry { drawable: x::Drawable::Window(window) }))
+            .map_err(|e| e.to_string())?;
+        let origin = conn
+            .wait_for_reply(conn.send_request(&x::TranslateCoordinates {
+                src_window: window,
+                dst_window: root,
+                src_x: 0,
+                src_y: 0,
+            }))
+            .map_err(|e| e.to_string())?;
+
+        let pixmap: x::Pixmap = conn.generate_id();
+        conn.send_and_check_request(&composite::NameWindowPixmap { window, pixmap })
+            .map_err(|e| format!("Failed to read window {}: {}", window_id, e))?;
+        // The pixmap includes the border; the contents start inside it
+        let border = geometry.border_width() as i16;
+        let image = capture_drawable(conn, x::Drawable::Pixmap(pixmap), border, border, geometry.width(), geometry.height());
+        conn.send_request(&x::FreePixmap { pixmap });
+        let _ = conn.flush();
+
+        Ok(WindowCapture {
+            image: image?,
+            frame: Rect {
+                x: origin.dst_x() as f64,
+                y: origin.dst_y() as f64,
+                width: geometry.width() as f64,
+                height: geometry.height() as f64,
+            },
+        })
+    }
+
+    fn pointer() -> Option<x::QueryPointerReply> {
+        let display = connect().ok()?;
+        let root = root_screen(&display.conn, display.screen_num).ok()?;
+        let cookie = display.conn.send_request(&x::QueryPointer { window: root.root() });
+        display.conn.wait_for_reply(cookie).ok()
+    }
+
+    // Pointer position on the root window
+    pub fn query_pointer() -> Option<(f64, f64)> {
+        let reply = pointer()?;
+        Some((reply.root_x() as f64, reply.root_y() as f64))
+    }
+
+    pub fn primary_button_down() -> Option<bool> {
+        Some(pointer()?.mask().contains(x::KeyButMask::BUTTON1))
+    }
+
+    // Read a ZPixmap region of a drawable into RGBA. Only 24/32-bit TrueColor
+    // visuals (BGRX in memory) are supported, which covers Xvfb's default.
+    pub fn capture_drawable(
+        conn: &xcb::Connection,
+        drawable: x::Drawable,
+        x: i16,
+        y: i16,
+        width: u16,
+        height: u16,
+    ) -> Result<RgbaImage, String> {
+        let cookie = conn.send_request(&x::GetImage {
+            format: x::ImageFormat::ZPixmap,
+            drawable,
+            x,
+            y,
+            width,
+            height,
+            plane_mask: u32::MAX,
+        });
+        let reply = conn.wait_for_reply(cookie).map_err(|e| e.to_string())?;
+
+        if reply.depth() != 24 && reply.depth() != 32 {
+            return Err(format!("Unsupported X image depth {}", reply.depth()));
+        }
+
+        let data = reply.data();
+        let pixel_count = width as usize * height as usize;
+        if data.len() < pixel_count * 4 {
+            return Err("X image data is shorter than expected".to_string());
+        }
+
+        let mut rgba = Vec::with_capacity(pixel_count * 4);
+        for bgrx in data.chunks_exact(4).take(pixel_count) {
+            rgba.extend_from_slice(&[bgrx[2], bgrx[1], bgrx[0], 255]);
+        }
+
+        RgbaImage::from_raw(width as u32, height as u32, rgba)
+            .ok_or_else(|| "Failed to build image from X data".to_string())
+    }
+}
+
+// Serves fixed images instead of the real screen so the command layer can run
+// headlessly. Screens are laid out left to right; the first one is primary.
+pub struct FakeBackend {
+    screens: Mutex<Vec<FakeScreen>>,
+    cursor: Mutex<Option<(f64, f64)>>,
+    // Window id, frame and contents for capture_window
+    windows: Mutex<Vec<(u32, Rect, RgbaImage)>>,
+}
+
+struct FakeScreen {
+    info: ScreenInfo,
+    source: FakeSource,
+}
+
+enum FakeSource {
+    // In-memory screens are only built by tests
+    #[cfg_attr(not(test), allow(dead_code))]
+    Image(RgbaImage),
+    // Re-read on every capture so tests can swap the file between captures
+    File(PathBuf),
+}
+
+impl FakeBackend {
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn from_images(images: Vec<RgbaImage>) -> Self {
+        let mut x = 0;
+        let screens = images
+            .into_iter()
+            .enumerate()
+            .map(|(index, image)| {
+                let info = fake_screen_info(index as u32, x, image.width(), image.height());
+                x += image.width() as i32;
+                FakeScreen { info, source: FakeSource::Image(image) }
+            })
+            .collect();
+
+        Self { screens: Mutex::new(screens), cursor: Mutex::new(None), windows: Mutex::new(Vec::new()) }
+    }
+
+    // Loads every *.png in `dir` (sorted by file name) as one screen
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, String> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir.as_ref())
+            .map_err(|e| e.to_string())?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("png")))
+            .collect();
+        paths.sort();
+
+        if paths.is_empty() {
+            return Err(format!("No PNG files in {}", dir.as_ref().display()));
+        }
+
+        let mut x = 0;
+        let mut screens = Vec::new();
+        for (index, path) in paths.into_iter().enumerate() {
+            let (width, height) = image::image_dimensions(&path).map_err(|e| e.to_string())?;
+            screens.push(FakeScreen {
+                info: fake_screen_info(index as u32, x, width, height),
+                source: FakeSource::File(path),
+            });
+            x += width as i32;
+        }
+
+        Ok(Self { screens: Mutex::new(screens), cursor: Mutex::new(None), windows: Mutex::new(Vec::new()) })
+    }
+
+    // Make capture_window serve `image` for `window_id`
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn add_window(&self, window_id: u32, frame: Rect, image: RgbaImage) {
+        self.windows.lock().unwrap().push((window_id, frame, image));
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn set_cursor(&self, position: Option<(f64, f64)>) {
+        *self.cursor.lock().unwrap() = position;
+    }
+
+    // Replace what a screen shows from now on
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn set_image(&self, screen_id: u32, image: RgbaImage) -> Result<(), String> {
+        let mut screens = self.screens.lock().unwrap();
+        let screen = screens
+            .iter_mut()
+            .find(|s| s.info.id == screen_id)
+            .ok_or_else(|| format!("Screen {} not found", screen_id))?;
+
+        screen.info.width = image.width();
+        screen.info.height = image.height();
+        screen.source = FakeSource::Image(image);
+        Ok(())
+    }
+}
+
+fn fake_screen_info(id: u32, x: i32, width: u32, height: u32) -> ScreenInfo {
+    ScreenInfo {
+        id,
+        x,
+        y: 0,
+        width,
+        height,
+        scale_factor: 1.0,
+        is_primary: id == 0,
+    }
+}
+
+impl CaptureBackend for FakeBackend {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    fn screens(&self) -> Result<Vec<ScreenInfo>, String> {
+        Ok(self.screens.lock().unwrap().iter().map(|s| s.info.clone()).collect())
+    }
+
+    fn capture_screen(&self, screen_id: u32) -> Result<RgbaImage, String> {
+        let screens = self.screens.lock().unwrap();
+        let screen = screens
+            .iter()
+            .find(|s| s.info.id == screen_id)
+            .ok_or_else(|| format!("Screen {} not found", screen_id))?;
+
+        match &screen.source {
+            FakeSource::Image(image) => Ok(image.clone()),
+            FakeSource::File(path) => image::open(path)
+                .map(|image| image.to_rgba8())
+                .map_err(|e| format!("Failed to load {}: {}", path.display(), e)),
+        }
+    }
+
+    fn cursor_position(&self) -> Option<(f64, f64)> {
+        *self.cursor.lock().unwrap()
+    }
+
+    fn capture_window(&self, window_id: u32) -> Result<WindowCapture, String> {
+        self.windows
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(id, _, _)| *id == window_id)
+            .map(|(_, frame, image)| WindowCapture { image: image.clone(), frame: *frame })
+            .ok_or_else(|| format!("Window {} not found", window_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use screenshots::image::Rgba;
+
+    fn solid(width: u32, height: u32, value: u8) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, Rgba([value, value, value, 255]))
+    }
+
+    #[test]
+    fn fake_backend_lays_out_screens_left_to_right() {
+        let backend = FakeBackend::from_images(vec![solid(100, 50, 0), solid(80, 60, 255)]);
+        let screens = backend.screens().unwrap();
+
+        assert_eq!(screens.len(), 2);
+        assert!(screens[0].is_primary);
+        assert_eq!((screens[1].x, screens[1].width, screens[1].height), (100, 80, 60));
+    }
+
+    #[test]
+    fn fake_backend_serves_and_replaces_images() {
+        let backend = FakeBackend::from_images(vec![solid(10, 10, 0)]);
+        assert_eq!(backend.capture_screen(0).unwrap().get_pixel(0, 0)[0], 0);
+
+        backend.set_image(0, solid(10, 10, 200)).unwrap();
+        assert_eq!(backend.capture_screen(0).unwrap().get_pixel(0, 0)[0], 200);
+        assert!(backend.capture_screen(1).is_err());
+    }
+
+    #[test]
+    fn fake_backend_reads_pngs_from_disk() {
+        let dir = std::env::temp_dir().join(format!("prism-fake-backend-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        solid(40, 30, 7).save(dir.join("screen-0.png")).unwrap();
+
+        let backend = FakeBackend::from_dir(&dir).unwrap();
+        let image = backend.capture_screen(0).unwrap();
+
+        assert_eq!(image.dimensions(), (40, 30));
+        assert_eq!(image.get_pixel(5, 5)[0], 7);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
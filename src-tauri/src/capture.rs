@@ -0,0 +1,274 @@
+use screenshots::image::{Rgba, RgbaImage};
+use screenshots::Screen;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::encode::EncodeReport;
+use crate::exclusion::ExcludedWindow;
+use crate::redaction::AppliedRedaction;
+use crate::{FocusedWindowInfo, Point};
+
+// Display description returned by list_screens
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenInfo {
+    pub id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f32,
+    pub is_primary: bool,
+}
+
+impl From<&Screen> for ScreenInfo {
+    fn from(screen: &Screen) -> Self {
+        let info = &screen.display_info;
+        Self {
+            id: info.id,
+            x: info.x,
+            y: info.y,
+            width: info.width,
+            height: info.height,
+            scale_factor: info.scale_factor,
+            is_primary: info.is_primary,
+        }
+    }
+}
+
+impl ScreenInfo {
+    pub fn bounds(&self) -> Rect {
+        Rect {
+            x: self.x as f64,
+            y: self.y as f64,
+            width: self.width as f64,
+            height: self.height as f64,
+        }
+    }
+}
+
+// Rectangle in logical desktop coordinates (points on macOS)
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    // Smallest rectangle containing every point
+    pub fn enclosing(points: &[(f64, f64)]) -> Rect {
+        let x0 = points.iter().map(|p| p.0).fold(f64::MAX, f64::min);
+        let y0 = points.iter().map(|p| p.1).fold(f64::MAX, f64::min);
+        let x1 = points.iter().map(|p| p.0).fold(f64::MIN, f64::max);
+        let y1 = points.iter().map(|p| p.1).fold(f64::MIN, f64::max);
+        Rect { x: x0, y: y0, width: x1 - x0, height: y1 - y0 }
+    }
+
+    pub fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let x1 = self.x.max(other.x);
+        let y1 = self.y.max(other.y);
+        let x2 = (self.x + self.width).min(other.x + other.width);
+        let y2 = (self.y + self.height).min(other.y + other.height);
+
+        if x2 > x1 && y2 > y1 {
+            Some(Rect { x: x1, y: y1, width: x2 - x1, height: y2 - y1 })
+        } else {
+            None
+        }
+    }
+}
+
+// Which display a capture or overlay should target.
+// Accepts either a numeric screen id or one of "primary" / "focused" from the frontend.
+#[derive(Clone, Copy, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum ScreenSelector {
+    Id(u32),
+    Named(NamedScreen),
+}
+
+#[derive(Clone, Copy, Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum NamedScreen {
+    Primary,
+    Focused,
+}
+
+// Pick the screen for a selector. `focused_point` is a desktop point inside the
+// focused window (if known); without it "focused" falls back to the primary screen.
+pub fn resolve_screen(
+    screens: &[ScreenInfo],
+    selector: Option<ScreenSelector>,
+    focused_point: Option<(f64, f64)>,
+) -> Result<&ScreenInfo, String> {
+    let primary = || {
+        screens
+            .iter()
+            .find(|s| s.is_primary)
+            .or_else(|| screens.first())
+            .ok_or_else(|| "No screens found".to_string())
+    };
+
+    match selector.unwrap_or(ScreenSelector::Named(NamedScreen::Focused)) {
+        ScreenSelector::Id(id) => screens
+            .iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| format!("Screen {} not found", id)),
+        ScreenSelector::Named(NamedScreen::Primary) => primary(),
+        ScreenSelector::Named(NamedScreen::Focused) => match focused_point {
+            Some((x, y)) => match screens.iter().find(|s| s.bounds().contains(x, y)) {
+                Some(screen) => Ok(screen),
+                None => primary(),
+            },
+            None => primary(),
+        },
+    }
+}
+
+// Area to capture on a screen: the focused window's bounds when they overlap the
+// screen, otherwise the whole screen
+pub fn capture_area(screen: &ScreenInfo, window: Option<Rect>) -> Rect {
+    let bounds = screen.bounds();
+    window
+        .and_then(|window| window.intersect(&bounds))
+        .unwrap_or(bounds)
+}
+
+// Rectangle in physical pixels of a screen capture
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// Convert a logical desktop area on `screen` into a pixel crop of a capture of
+// that screen, which may be larger than the logical size on HiDPI displays
+pub fn physical_crop(
+    area: &Rect,
+    screen: &ScreenInfo,
+    image_width: u32,
+    image_height: u32,
+) -> PixelRect {
+    let scale_x = image_width as f64 / screen.width.max(1) as f64;
+    let scale_y = image_height as f64 / screen.height.max(1) as f64;
+
+    let x = (((area.x - screen.x as f64) * scale_x).round().max(0.0) as u32).min(image_width);
+    let y = (((area.y - screen.y as f64) * scale_y).round().max(0.0) as u32).min(image_height);
+    let width = ((area.width * scale_x).round() as u32).min(image_width - x);
+    let height = ((area.height * scale_y).round() as u32).min(image_height - y);
+
+    PixelRect { x, y, width, height }
+}
+
+// Black out a logical desktop area in a full-screen capture of `screen`.
+// Returns false when the area is not on that screen.
+pub fn mask_area(image: &mut RgbaImage, area: &Rect, screen: &ScreenInfo) -> bool {
+    let Some(visible) = area.intersect(&screen.bounds()) else {
+        return false;
+    };
+
+    let pixels = physical_crop(&visible, screen, image.width(), image.height());
+    for y in pixels.y..pixels.y + pixels.height {
+        for x in pixels.x..pixels.x + pixels.width {
+            image.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+        }
+    }
+
+    true
+}
+
+// Where the pixels of a capture came from
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureSource {
+    // Whatever was on screen, including windows covering the target
+    #[default]
+    Screen,
+    // The focused window's own contents, read from the window server
+    Window,
+    // An image file opened with load_image_file, e.g. a customer's screenshot
+    File,
+    // An image pasted from the clipboard
+    Clipboard,
+}
+
+impl CaptureSource {
+    // Whether the capture shows this machine's desktop, so desktop rules and
+    // overlays apply to it
+    pub fn is_desktop(self) -> bool {
+        matches!(self, CaptureSource::Screen | CaptureSource::Window)
+    }
+}
+
+// Everything needed to map normalized coordinates in a capture back to the desktop:
+// a point (px, py) in 0..1 lands at (cropLogical.x + px * cropLogical.width, ...)
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureInfo {
+    pub capture_id: String,
+    // Chat or walkthrough the capture was taken for, used to group history
+    #[serde(default)]
+    pub session_id: Option<String>,
+    pub screen_id: u32,
+    // Crop rectangle in physical pixels of the screen (or window) capture
+    pub crop: PixelRect,
+    // Same rectangle in logical desktop coordinates
+    pub crop_logical: Rect,
+    // Physical pixels per logical point used for this capture
+    pub scale_factor: f64,
+    // Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub focused_window: Option<FocusedWindowInfo>,
+    // How the image was encoded (format, quality, final size)
+    pub encoding: EncodeReport,
+    // Prism windows kept out of the image and how
+    pub excluded_windows: Vec<ExcludedWindow>,
+    // Redaction rules applied to the image, for auditing
+    pub redactions: Vec<AppliedRedaction>,
+    // Pointer position normalized like model points, when it was drawn into the image
+    #[serde(default)]
+    pub cursor: Option<Point>,
+    #[serde(default)]
+    pub source: CaptureSource,
+    // Why the focused window could not be captured directly, when this fell back to the screen
+    #[serde(default)]
+    pub window_capture_error: Option<String>,
+}
+
+// Capture returned to the frontend. The image itself stays in the capture store;
+// `url` loads it through the prism-capture:// protocol.
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureResult {
+    pub url: String,
+    #[serde(flatten)]
+    pub info: CaptureInfo,
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// Unique id for a capture: timestamp plus a process-wide counter
+pub fn next_capture_id(timestamp: u64) -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    format!("{}-{}", timestamp, COUNTER.fetch_add(1, Ordering::Relaxed))
+}
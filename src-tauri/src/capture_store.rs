@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use crate::capture::CaptureInfo;
+use crate::change::Fingerprint;
+
+// URI scheme the webview uses to load capture images by id
+pub const CAPTURE_SCHEME: &str = "prism-capture";
+
+// Keep at most this many captures, and this many encoded bytes, in memory
+const MAX_ENTRIES: usize = 64;
+const MAX_TOTAL_BYTES: usize = 256 * 1024 * 1024;
+
+#[derive(Clone)]
+pub struct StoredCapture {
+    pub info: CaptureInfo,
+    pub mime_type: &'static str,
+    pub bytes: Arc<Vec<u8>>,
+    // Computed from the cropped image before encoding, for compare_captures
+    pub fingerprint: Fingerprint,
+}
+
+// Encoded capture images held on the Rust side, oldest evicted first
+pub struct CaptureStore {
+    entries: Mutex<VecDeque<StoredCapture>>,
+}
+
+impl CaptureStore {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn insert(
+        &self,
+        info: CaptureInfo,
+        mime_type: &'static str,
+        bytes: Vec<u8>,
+        fingerprint: Fingerprint,
+    ) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.push_back(StoredCapture {
+            info,
+            mime_type,
+            bytes: Arc::new(bytes),
+            fingerprint,
+        });
+
+        let mut total: usize = entries.iter().map(|e| e.bytes.len()).sum();
+        while entries.len() > 1 && (entries.len() > MAX_ENTRIES || total > MAX_TOTAL_BYTES) {
+            if let Some(evicted) = entries.pop_front() {
+                total -= evicted.bytes.len();
+            }
+        }
+    }
+
+    pub fn get(&self, capture_id: &str) -> Option<StoredCapture> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|e| e.info.capture_id == capture_id)
+            .cloned()
+    }
+
+    pub fn remove(&self, capture_ids: &[String]) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|e| !capture_ids.contains(&e.info.capture_id));
+    }
+
+    pub fn info(&self, capture_id: &str) -> Option<CaptureInfo> {
+        self.get(capture_id).map(|e| e.info)
+    }
+
+    // Most recent capture of the screen or a window, skipping imported images
+    pub fn latest_desktop(&self) -> Option<StoredCapture> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|e| e.info.source.is_desktop())
+            .cloned()
+    }
+}
+
+// URL the webview can use as an <img> source for a stored capture
+pub fn capture_url(capture_id: &str) -> String {
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://{}.localhost/{}", CAPTURE_SCHEME, capture_id)
+    } else {
+        format!("{}://localhost/{}", CAPTURE_SCHEME, capture_id)
+    }
+}
@@ -0,0 +1,235 @@
+use screenshots::image::RgbaImage;
+use serde::Serialize;
+
+use crate::capture::Rect;
+
+// Cells per side of the block grid used for change detection
+const GRID: usize = 32;
+// dHash works on a 9x8 grid: each bit compares two horizontal neighbours
+const HASH_COLS: usize = 9;
+const HASH_ROWS: usize = 8;
+// Mean brightness change (0-255) a cell needs before it counts as changed.
+// High enough to ignore a blinking caret or antialiasing noise.
+pub const DEFAULT_THRESHOLD: u8 = 10;
+
+// Compact summary of a capture used to tell whether the screen changed
+#[derive(Clone, Debug)]
+pub struct Fingerprint {
+    // 64-bit difference hash of the whole image
+    pub hash: u64,
+    // Mean luma of each GRID x GRID cell, row-major
+    cells: Vec<u8>,
+}
+
+impl Fingerprint {
+    pub fn of(image: &RgbaImage) -> Self {
+        let means = cell_means(image, HASH_COLS, HASH_ROWS);
+        let mut hash = 0u64;
+        for row in 0..HASH_ROWS {
+            for col in 0..HASH_COLS - 1 {
+                let left = means[row * HASH_COLS + col];
+                let right = means[row * HASH_COLS + col + 1];
+                hash = (hash << 1) | (left > right) as u64;
+            }
+        }
+
+        let cells = cell_means(image, GRID, GRID)
+            .into_iter()
+            .map(|mean| mean.round() as u8)
+            .collect();
+
+        Self { hash, cells }
+    }
+}
+
+// Average luma of each cell when the image is divided into cols x rows
+fn cell_means(image: &RgbaImage, cols: usize, rows: usize) -> Vec<f64> {
+    let (width, height) = image.dimensions();
+    let mut sums = vec![0u64; cols * rows];
+    let mut counts = vec![0u64; cols * rows];
+
+    if width == 0 || height == 0 {
+        return vec![0.0; cols * rows];
+    }
+
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let col = x as usize * cols / width as usize;
+        let row = y as usize * rows / height as usize;
+        let [r, g, b, _] = pixel.0;
+        // Rec. 601 luma in fixed point
+        let luma = (299 * r as u64 + 587 * g as u64 + 114 * b as u64) / 1000;
+        sums[row * cols + col] += luma;
+        counts[row * cols + col] += 1;
+    }
+
+    sums.iter()
+        .zip(&counts)
+        .map(|(sum, count)| *sum as f64 / (*count).max(1) as f64)
+        .collect()
+}
+
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedRegion {
+    // Normalized 0..1 to the newer capture, like model points
+    pub bounds: Rect,
+    // The same region in logical desktop coordinates
+    pub desktop: Rect,
+    // Number of grid cells that changed inside the region
+    pub cells: usize,
+}
+
+// Result of compare_captures
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeReport {
+    pub changed: bool,
+    // Fraction of grid cells that changed, 0..1
+    pub score: f64,
+    // Hamming distance between the two difference hashes, 0..64
+    pub hash_distance: u32,
+    pub regions: Vec<ChangedRegion>,
+}
+
+// Compare two fingerprints. `area` is the logical desktop rectangle of the
+// newer capture, used to place the changed regions on screen.
+pub fn compare(before: &Fingerprint, after: &Fingerprint, threshold: u8, area: &Rect) -> ChangeReport {
+    let changed_cells: Vec<bool> = before
+        .cells
+        .iter()
+        .zip(&after.cells)
+        .map(|(a, b)| a.abs_diff(*b) > threshold)
+        .collect();
+
+    let changed_count = changed_cells.iter().filter(|c| **c).count();
+    let regions = connected_regions(&changed_cells)
+        .into_iter()
+        .map(|(col_min, row_min, col_max, row_max, cells)| {
+            let bounds = Rect {
+                x: col_min as f64 / GRID as f64,
+                y: row_min as f64 / GRID as f64,
+                width: (col_max - col_min + 1) as f64 / GRID as f64,
+                height: (row_max - row_min + 1) as f64 / GRID as f64,
+            };
+            let desktop = Rect {
+                x: area.x + bounds.x * area.width,
+                y: area.y + bounds.y * area.height,
+                width: bounds.width * area.width,
+                height: bounds.height * area.height,
+            };
+            ChangedRegion { bounds, desktop, cells }
+        })
+        .collect::<Vec<_>>();
+
+    ChangeReport {
+        changed: !regions.is_empty(),
+        score: changed_count as f64 / changed_cells.len().max(1) as f64,
+        hash_distance: (before.hash ^ after.hash).count_ones(),
+        regions,
+    }
+}
+
+// Group changed cells into 8-connected components.
+// Returns (col_min, row_min, col_max, row_max, cell_count) per component.
+fn connected_regions(changed: &[bool]) -> Vec<(usize, usize, usize, usize, usize)> {
+    let mut seen = vec![false; changed.len()];
+    let mut regions = Vec::new();
+
+    for start in 0..changed.len() {
+        if !changed[start] || seen[start] {
+            continue;
+        }
+
+        let mut stack = vec![start];
+        seen[start] = true;
+        let (mut col_min, mut row_min) = (GRID, GRID);
+        let (mut col_max, mut row_max) = (0, 0);
+        let mut count = 0;
+
+        while let Some(index) = stack.pop() {
+            let (col, row) = (index % GRID, index / GRID);
+            col_min = col_min.min(col);
+            col_max = col_max.max(col);
+            row_min = row_min.min(row);
+            row_max = row_max.max(row);
+            count += 1;
+
+            for dy in -1i32..=1 {
+                for dx in -1i32..=1 {
+                    let (nx, ny) = (col as i32 + dx, row as i32 + dy);
+                    if nx < 0 || ny < 0 || nx >= GRID as i32 || ny >= GRID as i32 {
+                        continue;
+                    }
+                    let neighbour = ny as usize * GRID + nx as usize;
+                    if changed[neighbour] && !seen[neighbour] {
+                        seen[neighbour] = true;
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+
+        regions.push((col_min, row_min, col_max, row_max, count));
+    }
+
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use screenshots::image::Rgba;
+
+    const AREA: Rect = Rect { x: 100.0, y: 50.0, width: 320.0, height: 320.0 };
+
+    fn gradient() -> RgbaImage {
+        RgbaImage::from_fn(640, 640, |x, y| {
+            let v = ((x + y) / 6) as u8;
+            Rgba([v, v, v, 255])
+        })
+    }
+
+    #[test]
+    fn identical_images_are_unchanged() {
+        let image = gradient();
+        let report = compare(&Fingerprint::of(&image), &Fingerprint::of(&image), DEFAULT_THRESHOLD, &AREA);
+
+        assert!(!report.changed);
+        assert_eq!(report.score, 0.0);
+        assert_eq!(report.hash_distance, 0);
+    }
+
+    #[test]
+    fn small_noise_is_ignored() {
+        let before = gradient();
+        let mut after = before.clone();
+        // A caret-sized mark inside one cell
+        for y in 42..56 {
+            after.put_pixel(30, y, Rgba([255, 255, 255, 255]));
+        }
+
+        let report = compare(&Fingerprint::of(&before), &Fingerprint::of(&after), DEFAULT_THRESHOLD, &AREA);
+        assert!(!report.changed);
+    }
+
+    #[test]
+    fn reports_changed_region_in_both_coordinate_spaces() {
+        let before = gradient();
+        let mut after = before.clone();
+        // Fill the 4x2 cells starting at cell (8, 16) with blue
+        for y in 320..360 {
+            for x in 160..240 {
+                after.put_pixel(x, y, Rgba([0, 0, 255, 255]));
+            }
+        }
+
+        let report = compare(&Fingerprint::of(&before), &Fingerprint::of(&after), DEFAULT_THRESHOLD, &AREA);
+
+        assert!(report.changed);
+        assert_eq!(report.regions.len(), 1);
+        let region = &report.regions[0];
+        assert_eq!(region.cells, 8);
+        assert_eq!(region.bounds, Rect { x: 0.25, y: 0.5, width: 0.125, height: 0.0625 });
+        assert_eq!(region.desktop, Rect { x: 180.0, y: 210.0, width: 40.0, height: 20.0 });
+    }
+}
@@ -0,0 +1,41 @@
+use screenshots::image::RgbaImage;
+use std::borrow::Cow;
+use std::sync::Mutex;
+
+// Lazily opened system clipboard. The handle is kept for the life of the app
+// because on X11 copied data is only served while it is alive.
+pub struct ClipboardAccess {
+    clipboard: Mutex<Option<arboard::Clipboard>>,
+}
+
+impl ClipboardAccess {
+    pub fn new() -> Self {
+        Self { clipboard: Mutex::new(None) }
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>) -> Result<T, String> {
+        let mut clipboard = self.clipboard.lock().unwrap();
+        if clipboard.is_none() {
+            *clipboard = Some(arboard::Clipboard::new().map_err(|e| format!("Clipboard unavailable: {}", e))?);
+        }
+        f(clipboard.as_mut().unwrap()).map_err(|e| e.to_string())
+    }
+
+    pub fn read_image(&self) -> Result<RgbaImage, String> {
+        let image = self.with(|clipboard| clipboard.get_image()).map_err(|e| {
+            format!("No image on the clipboard: {}", e)
+        })?;
+
+        RgbaImage::from_raw(image.width as u32, image.height as u32, image.bytes.into_owned())
+            .ok_or_else(|| "Clipboard image has an unexpected size".to_string())
+    }
+
+    pub fn write_image(&self, image: &RgbaImage) -> Result<(), String> {
+        let data = arboard::ImageData {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            bytes: Cow::Borrowed(image.as_raw()),
+        };
+        self.with(|clipboard| clipboard.set_image(data))
+    }
+}
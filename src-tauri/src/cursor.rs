@@ -0,0 +1,268 @@
+use screenshots::image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::async_runtime::JoinHandle;
+use tauri::{Emitter, Manager};
+
+use crate::capture::{Rect, ScreenInfo};
+use crate::{overlay, AppState, Point};
+
+// Classic arrow pointer outline in logical pixels, tip at the origin
+const ARROW: [(f64, f64); 7] = [
+    (0.0, 0.0),
+    (0.0, 17.0),
+    (4.0, 13.0),
+    (7.0, 20.0),
+    (10.0, 19.0),
+    (7.0, 12.0),
+    (12.0, 12.0),
+];
+const OUTLINE_WIDTH: f64 = 1.2;
+// Polling faster than this floods the webview with events
+const MIN_INTERVAL_MS: u64 = 16;
+
+// Draw an arrow pointer with its tip at a logical desktop point on `screen`
+pub fn draw_cursor(image: &mut RgbaImage, screen: &ScreenInfo, x: f64, y: f64) {
+    let scale = image.width() as f64 / screen.width.max(1) as f64;
+    let tip_x = (x - screen.x as f64) * scale;
+    let tip_y = (y - screen.y as f64) * scale;
+    let polygon: Vec<(f64, f64)> = ARROW
+        .iter()
+        .map(|(px, py)| (tip_x + px * scale, tip_y + py * scale))
+        .collect();
+
+    let bounds = Rect::enclosing(&polygon);
+    let min_x = bounds.x.floor().max(0.0) as u32;
+    let min_y = bounds.y.floor().max(0.0) as u32;
+    let max_x = ((bounds.x + bounds.width).ceil() as u32).min(image.width());
+    let max_y = ((bounds.y + bounds.height).ceil() as u32).min(image.height());
+
+    for py in min_y..max_y {
+        for px in min_x..max_x {
+            let (cx, cy) = (px as f64 + 0.5, py as f64 + 0.5);
+            if !inside(&polygon, cx, cy) {
+                continue;
+            }
+            let color = if edge_distance(&polygon, cx, cy) < OUTLINE_WIDTH * scale {
+                Rgba([0, 0, 0, 255])
+            } else {
+                Rgba([255, 255, 255, 255])
+            };
+            image.put_pixel(px, py, color);
+        }
+    }
+}
+
+// Even-odd point-in-polygon test
+pub fn inside(polygon: &[(f64, f64)], x: f64, y: f64) -> bool {
+    let mut result = false;
+    let mut j = polygon.len() - 1;
+    for i in 0..polygon.len() {
+        let (xi, yi) = polygon[i];
+        let (xj, yj) = polygon[j];
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            result = !result;
+        }
+        j = i;
+    }
+    result
+}
+
+fn edge_distance(polygon: &[(f64, f64)], x: f64, y: f64) -> f64 {
+    (0..polygon.len())
+        .map(|i| {
+            let (ax, ay) = polygon[i];
+            let (bx, by) = polygon[(i + 1) % polygon.len()];
+            let (dx, dy) = (bx - ax, by - ay);
+            let t = (((x - ax) * dx + (y - ay) * dy) / (dx * dx + dy * dy)).clamp(0.0, 1.0);
+            ((ax + t * dx - x).powi(2) + (ay + t * dy - y).powi(2)).sqrt()
+        })
+        .fold(f64::MAX, f64::min)
+}
+
+// Normalize a desktop point to `area`, the same space as model Points
+pub fn normalize(area: &Rect, x: f64, y: f64) -> Point {
+    Point {
+        x: (x - area.x) / area.width.max(1.0),
+        y: (y - area.y) / area.height.max(1.0),
+        ..Default::default()
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CursorStreamOptions {
+    // Minimum time between cursor-moved events
+    pub interval_ms: u64,
+    // Capture whose area defines the normalized space; defaults to the current capture area
+    pub capture_id: Option<String>,
+    // Normalized targets to measure the distance to; when empty, the points and
+    // box centers of the step the overlay currently shows
+    pub targets: Vec<Point>,
+}
+
+impl Default for CursorStreamOptions {
+    fn default() -> Self {
+        Self {
+            interval_ms: 50,
+            capture_id: None,
+            targets: Vec::new(),
+        }
+    }
+}
+
+// Payload of the cursor-moved event
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CursorMoved {
+    // Normalized to the reference area like Point; outside 0..1 when off the area
+    pub position: Point,
+    // Logical desktop coordinates
+    pub desktop_x: f64,
+    pub desktop_y: f64,
+    pub inside: bool,
+    // Nearest target and its distance in logical pixels
+    pub target: Option<Point>,
+    pub distance: Option<f64>,
+}
+
+impl CursorMoved {
+    pub fn new(area: &Rect, x: f64, y: f64, targets: &[Point]) -> Self {
+        let nearest = targets
+            .iter()
+            .map(|target| {
+                let dx = (target.x - (x - area.x) / area.width.max(1.0)) * area.width;
+                let dy = (target.y - (y - area.y) / area.height.max(1.0)) * area.height;
+                (target, (dx * dx + dy * dy).sqrt())
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        Self {
+            position: normalize(area, x, y),
+            desktop_x: x,
+            desktop_y: y,
+            inside: area.contains(x, y),
+            target: nearest.map(|(target, _)| target.clone()),
+            distance: nearest.map(|(_, distance)| distance),
+        }
+    }
+}
+
+// Background task that polls the pointer and emits cursor-moved when it moves
+pub struct CursorStream {
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl CursorStream {
+    pub fn new() -> Self {
+        Self { task: Mutex::new(None) }
+    }
+
+    pub fn start(&self, app: tauri::AppHandle, options: CursorStreamOptions, area: Rect) {
+        let task = tauri::async_runtime::spawn(run(app, options, area));
+        if let Some(previous) = self.task.lock().unwrap().replace(task) {
+            previous.abort();
+        }
+    }
+
+    pub fn stop(&self) -> bool {
+        match self.task.lock().unwrap().take() {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+// The points and box centers of the step the overlay shows, normalized to `area`
+fn step_targets(state: &AppState, area: &Rect) -> Vec<Point> {
+    let current = state.overlay.lock().unwrap().clone();
+    let (Some(payload), Some(step_area)) = (&current.payload, &current.area) else {
+        return Vec::new();
+    };
+    let screens = state.backend.screens().unwrap_or_default();
+    let mapped = overlay::normalize_to(payload, step_area, &screens, area);
+
+    let centers = mapped.boxes.iter().map(|b| Point {
+        x: (b.x_min + b.x_max) / 2.0,
+        y: (b.y_min + b.y_max) / 2.0,
+        ..Default::default()
+    });
+    mapped.points.iter().cloned().chain(centers).collect()
+}
+
+async fn run(app: tauri::AppHandle, options: CursorStreamOptions, area: Rect) {
+    let interval = options.interval_ms.max(MIN_INTERVAL_MS);
+    let mut ticker = tokio::time::interval(tokio::time::Duration::from_millis(interval));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut last = None;
+    let mut targets = options.targets.clone();
+    let mut step_generation = None;
+
+    loop {
+        ticker.tick().await;
+        let state = app.state::<AppState>();
+
+        // Follow the overlay to each new step; every step update bumps the generation
+        if options.targets.is_empty() {
+            let generation = state.overlay.lock().unwrap().generation;
+            if step_generation != Some(generation) {
+                step_generation = Some(generation);
+                targets = step_targets(&state, &area);
+                last = None;
+            }
+        }
+
+        let Some(position) = state.backend.cursor_position() else {
+            continue;
+        };
+        if last == Some(position) {
+            continue;
+        }
+        last = Some(position);
+
+        let event = CursorMoved::new(&area, position.0, position.1, &targets);
+        let _ = app.emit("cursor-moved", event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_nearest_target_in_logical_pixels() {
+        let area = Rect { x: 100.0, y: 100.0, width: 400.0, height: 200.0 };
+        let targets = vec![Point { x: 0.5, y: 0.5, ..Default::default() }, Point { x: 0.9, y: 0.5, ..Default::default() }];
+
+        let event = CursorMoved::new(&area, 200.0, 200.0, &targets);
+
+        assert_eq!((event.position.x, event.position.y), (0.25, 0.5));
+        assert!(event.inside);
+        assert_eq!(event.target.map(|t| t.x), Some(0.5));
+        assert_eq!(event.distance, Some(100.0));
+        assert!(!CursorMoved::new(&area, 50.0, 50.0, &targets).inside);
+    }
+
+    #[test]
+    fn reads_targets_from_the_current_step() {
+        let screen = RgbaImage::new(800, 600);
+        let state = AppState::with_backend(Box::new(crate::backend::FakeBackend::from_images(vec![screen])));
+        let area = Rect { x: 0.0, y: 0.0, width: 800.0, height: 600.0 };
+        assert!(step_targets(&state, &area).is_empty());
+
+        let payload = crate::OverlayPayload {
+            points: vec![Point { x: 0.5, y: 0.5, ..Default::default() }],
+            boxes: vec![crate::BoundingBox { x_min: 0.0, y_min: 0.0, x_max: 0.5, y_max: 0.5, ..Default::default() }],
+            ..Default::default()
+        };
+        state.overlay.lock().unwrap().set_step(area, payload);
+
+        // Normalized to the stream's area, here the left half of the step's
+        let half = Rect { width: 400.0, ..area };
+        let targets: Vec<(f64, f64)> = step_targets(&state, &half).iter().map(|p| (p.x, p.y)).collect();
+        assert_eq!(targets, [(1.0, 0.5), (0.5, 0.25)]);
+    }
+}
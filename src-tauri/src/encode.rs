@@ -0,0 +1,217 @@
+use screenshots::image::codecs::jpeg::JpegEncoder;
+use screenshots::image::codecs::png::PngEncoder;
+use screenshots::image::codecs::webp::WebPEncoder;
+use screenshots::image::imageops::{self, FilterType};
+use screenshots::image::{ColorType, DynamicImage, ImageEncoder, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+// Lowest JPEG quality the byte budget loop will go down to before shrinking the image
+const MIN_JPEG_QUALITY: u8 = 40;
+const QUALITY_STEP: u8 = 10;
+// Each shrink step scales the long edge by this factor
+const SHRINK_FACTOR: f64 = 0.8;
+// Never shrink below this long edge, even if the budget is not met
+const MIN_LONG_EDGE: u32 = 320;
+const MAX_ATTEMPTS: u32 = 12;
+const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Png,
+    Jpeg,
+    // Lossless only: the encoder has no lossy mode, so `quality` is rejected
+    // and a byte budget is met by shrinking the image alone
+    Webp,
+}
+
+impl OutputFormat {
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "image/png",
+            OutputFormat::Jpeg => "image/jpeg",
+            OutputFormat::Webp => "image/webp",
+        }
+    }
+}
+
+// How take_screenshot should encode the captured image
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EncodeOptions {
+    // Downscale so the longer side is at most this many pixels
+    pub max_long_edge: Option<u32>,
+    pub format: OutputFormat,
+    // 1-100, JPEG only; other formats are lossless and reject it
+    pub quality: Option<u8>,
+    // Lower JPEG quality, then size, until the encoded image fits
+    pub max_bytes: Option<usize>,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        Self {
+            max_long_edge: None,
+            format: OutputFormat::Png,
+            quality: None,
+            max_bytes: None,
+        }
+    }
+}
+
+// Parameters actually used for the encoded image
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EncodeReport {
+    pub format: OutputFormat,
+    pub quality: Option<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub bytes: usize,
+    pub attempts: u32,
+    // False when max_bytes was set and could not be reached
+    pub within_budget: bool,
+}
+
+fn validate(options: &EncodeOptions) -> Result<(), String> {
+    match (options.format, options.quality) {
+        (_, None) => Ok(()),
+        (OutputFormat::Jpeg, Some(quality)) if (1..=100).contains(&quality) => Ok(()),
+        (OutputFormat::Jpeg, Some(quality)) => Err(format!("JPEG quality must be 1-100, got {}", quality)),
+        (format, Some(_)) => Err(format!("quality only applies to JPEG; {:?} is lossless", format)),
+    }
+}
+
+fn encode_once(image: &RgbaImage, format: OutputFormat, quality: u8) -> Result<Vec<u8>, String> {
+    let mut bytes: Vec<u8> = Vec::new();
+    let (width, height) = image.dimensions();
+
+    match format {
+        OutputFormat::Png => PngEncoder::new(&mut bytes)
+            .write_image(image.as_raw(), width, height, ColorType::Rgba8)
+            .map_err(|e| e.to_string())?,
+        OutputFormat::Jpeg => {
+            // JPEG has no alpha channel
+            let rgb = DynamicImage::ImageRgba8(image.clone()).to_rgb8();
+            JpegEncoder::new_with_quality(&mut bytes, quality)
+                .write_image(rgb.as_raw(), width, height, ColorType::Rgb8)
+                .map_err(|e| e.to_string())?
+        }
+        OutputFormat::Webp => WebPEncoder::new_lossless(&mut bytes)
+            .write_image(image.as_raw(), width, height, ColorType::Rgba8)
+            .map_err(|e| e.to_string())?,
+    }
+
+    Ok(bytes)
+}
+
+fn resize_to_long_edge(image: &RgbaImage, long_edge: u32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let current = width.max(height);
+    if current <= long_edge {
+        return image.clone();
+    }
+
+    let scale = long_edge as f64 / current as f64;
+    let new_width = ((width as f64 * scale).round() as u32).max(1);
+    let new_height = ((height as f64 * scale).round() as u32).max(1);
+    imageops::resize(image, new_width, new_height, FilterType::Triangle)
+}
+
+// Encode an image according to `options`. With a byte budget, JPEG quality is
+// lowered first, then the image is shrunk step by step until it fits.
+pub fn encode(image: &RgbaImage, options: &EncodeOptions) -> Result<(Vec<u8>, EncodeReport), String> {
+    validate(options)?;
+    let base = match options.max_long_edge {
+        Some(long_edge) => resize_to_long_edge(image, long_edge.max(1)),
+        None => image.clone(),
+    };
+    let mut current = base.clone();
+    let mut quality = options.quality.unwrap_or(DEFAULT_JPEG_QUALITY);
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+        let bytes = encode_once(&current, options.format, quality)?;
+
+        let fits = options.max_bytes.map_or(true, |budget| bytes.len() <= budget);
+        let long_edge = current.width().max(current.height());
+        let can_shrink = long_edge > MIN_LONG_EDGE;
+        let can_lower_quality = options.format == OutputFormat::Jpeg && quality > MIN_JPEG_QUALITY;
+
+        if fits || attempts >= MAX_ATTEMPTS || !(can_shrink || can_lower_quality) {
+            let report = EncodeReport {
+                format: options.format,
+                quality: (options.format == OutputFormat::Jpeg).then_some(quality),
+                width: current.width(),
+                height: current.height(),
+                bytes: bytes.len(),
+                attempts,
+                within_budget: fits,
+            };
+            return Ok((bytes, report));
+        }
+
+        if can_lower_quality {
+            quality = quality.saturating_sub(QUALITY_STEP).max(MIN_JPEG_QUALITY);
+        } else {
+            let target = ((long_edge as f64 * SHRINK_FACTOR) as u32).max(MIN_LONG_EDGE);
+            // Always resample from the base so blur does not compound
+            current = resize_to_long_edge(&base, target);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use screenshots::image::Rgba;
+
+    fn solid(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, Rgba([30, 120, 200, 255]))
+    }
+
+    #[test]
+    fn resizes_to_the_long_edge_and_stops_once_within_budget() {
+        let options = EncodeOptions { max_long_edge: Some(100), max_bytes: Some(1 << 20), ..Default::default() };
+        let (_, report) = encode(&solid(400, 200), &options).unwrap();
+        assert_eq!((report.width, report.height), (100, 50));
+        assert_eq!((report.attempts, report.within_budget, report.quality), (1, true, None));
+
+        // Smaller images are left alone
+        let (_, report) = encode(&solid(80, 40), &options).unwrap();
+        assert_eq!((report.width, report.height), (80, 40));
+    }
+
+    #[test]
+    fn lowers_jpeg_quality_then_size_down_to_the_limits() {
+        let options = EncodeOptions { format: OutputFormat::Jpeg, max_bytes: Some(1), ..Default::default() };
+        let (bytes, report) = encode(&solid(400, 300), &options).unwrap();
+
+        // 85, 75, 65, 55, 45, 40, then one shrink from 400 to the 320 floor
+        assert_eq!(report.quality, Some(MIN_JPEG_QUALITY));
+        assert_eq!((report.width, report.height), (MIN_LONG_EDGE, 240));
+        assert_eq!(report.attempts, 7);
+        assert!(!report.within_budget);
+        assert_eq!(report.bytes, bytes.len());
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        // Lossless formats only shrink; a long strip needs more steps than allowed
+        for format in [OutputFormat::Png, OutputFormat::Webp] {
+            let options = EncodeOptions { format, max_bytes: Some(1), ..Default::default() };
+            let (_, report) = encode(&solid(4000, 10), &options).unwrap();
+            assert_eq!((report.attempts, report.quality, report.within_budget), (MAX_ATTEMPTS, None, false));
+            assert!(report.width > MIN_LONG_EDGE && report.width < 4000);
+        }
+    }
+
+    #[test]
+    fn rejects_quality_outside_jpeg() {
+        let options = EncodeOptions { format: OutputFormat::Webp, quality: Some(80), ..Default::default() };
+        assert!(encode(&solid(10, 10), &options).is_err());
+        let options = EncodeOptions { format: OutputFormat::Jpeg, quality: Some(0), ..Default::default() };
+        assert!(encode(&solid(10, 10), &options).is_err());
+    }
+}
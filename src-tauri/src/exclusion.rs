@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, WebviewWindow};
+
+use crate::capture::Rect;
+
+// Time for the window server to actually remove hidden windows from the screen
+const HIDE_SETTLE_MS: u64 = 80;
+
+// Whether content protection keeps the overlays out of screen captures. Linux
+// window servers ignore it, so the watcher masks what the overlay draws instead.
+pub const OVERLAYS_PROTECTED: bool = cfg!(any(target_os = "macos", target_os = "windows"));
+
+// How take_screenshot keeps Prism's own windows (main panel, settings,
+// overlays) out of the captured image
+#[derive(Clone, Copy, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExclusionMode {
+    // Hide windows, capture, then show them again; anything that fails to hide is masked
+    #[default]
+    Hide,
+    // Leave windows on screen and black out their rectangles in the image
+    Mask,
+    None,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExclusionMethod {
+    Hidden,
+    Masked,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcludedWindow {
+    pub label: String,
+    pub method: ExclusionMethod,
+}
+
+fn visible_windows(app: &tauri::AppHandle) -> Vec<WebviewWindow> {
+    app.webview_windows()
+        .into_values()
+        .filter(|window| window.is_visible().unwrap_or(false))
+        .collect()
+}
+
+// Hide every visible Prism window and wait for them to leave the screen.
+// Returns the windows that were hidden so they can be restored.
+pub async fn hide_windows(app: &tauri::AppHandle) -> Vec<WebviewWindow> {
+    let hidden: Vec<WebviewWindow> = visible_windows(app)
+        .into_iter()
+        .filter(|window| window.hide().is_ok())
+        .collect();
+
+    if !hidden.is_empty() {
+        tokio::time::sleep(tokio::time::Duration::from_millis(HIDE_SETTLE_MS)).await;
+    }
+
+    hidden
+}
+
+pub fn restore_windows(windows: &[WebviewWindow]) {
+    for window in windows {
+        if let Err(e) = window.show() {
+            println!("[Prism] Failed to restore window {}: {}", window.label(), e);
+        }
+    }
+}
+
+fn window_rect(window: &WebviewWindow) -> Option<(String, Rect)> {
+    let position = window.outer_position().ok()?;
+    let size = window.outer_size().ok()?;
+    let scale = window.scale_factor().unwrap_or(1.0);
+
+    Some((
+        window.label().to_string(),
+        Rect {
+            x: position.x as f64 / scale,
+            y: position.y as f64 / scale,
+            width: size.width as f64 / scale,
+            height: size.height as f64 / scale,
+        },
+    ))
+}
+
+// Logical desktop rectangles of Prism windows that are still on screen
+pub fn visible_window_rects(app: &tauri::AppHandle) -> Vec<(String, Rect)> {
+    visible_windows(app).iter().filter_map(window_rect).collect()
+}
+
+// Rectangles of every Prism window except the overlays, shown or not, so the
+// screen watcher masks the same pixels even while take_screenshot hides them.
+// Overlays are content protected and stay out of captures where the OS supports it.
+pub fn panel_window_rects(app: &tauri::AppHandle) -> Vec<(String, Rect)> {
+    app.webview_windows()
+        .into_values()
+        .filter(|window| !window.label().starts_with("screen-overlay"))
+        .filter_map(|window| window_rect(&window))
+        .collect()
+}
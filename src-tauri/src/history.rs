@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::capture::{self, CaptureInfo};
+use crate::capture_store;
+
+const HISTORY_DIR: &str = "captures";
+const POLICY_FILE: &str = "capture_retention.json";
+// How often the background sweeper enforces the retention policy
+pub const SWEEP_INTERVAL_SECS: u64 = 10 * 60;
+
+// How long captures are kept on disk. `None` disables a limit.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetentionPolicy {
+    pub max_age_hours: Option<u64>,
+    pub max_total_bytes: Option<u64>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_age_hours: Some(7 * 24),
+            max_total_bytes: Some(500 * 1024 * 1024),
+        }
+    }
+}
+
+// Sidecar record written next to each capture image
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureRecord {
+    #[serde(flatten)]
+    pub info: CaptureInfo,
+    pub mime_type: String,
+    // Image file name inside the history directory
+    pub file: String,
+    pub bytes: u64,
+}
+
+// Record returned to the frontend, with a URL that loads the image
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub url: String,
+    #[serde(flatten)]
+    pub record: CaptureRecord,
+}
+
+impl From<CaptureRecord> for HistoryEntry {
+    fn from(record: CaptureRecord) -> Self {
+        Self {
+            url: capture_store::capture_url(&record.info.capture_id),
+            record,
+        }
+    }
+}
+
+// Captures persisted under <app data>/captures as <id>.<ext> plus <id>.json
+pub struct CaptureHistory {
+    dir: Mutex<Option<PathBuf>>,
+    policy: Mutex<RetentionPolicy>,
+}
+
+// Capture ids come from URLs and commands; never let them name other files
+fn valid_id(capture_id: &str) -> bool {
+    !capture_id.is_empty()
+        && capture_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn extension(mime_type: &str) -> &'static str {
+    match mime_type {
+        "image/jpeg" => "jpg",
+        "image/webp" => "webp",
+        _ => "png",
+    }
+}
+
+impl CaptureHistory {
+    // History is disabled until a directory is set
+    pub fn new() -> Self {
+        Self {
+            dir: Mutex::new(None),
+            policy: Mutex::new(RetentionPolicy::default()),
+        }
+    }
+
+    pub fn open(&self, dir: PathBuf, policy: RetentionPolicy) -> Result<(), String> {
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        *self.dir.lock().unwrap() = Some(dir);
+        *self.policy.lock().unwrap() = policy;
+        Ok(())
+    }
+
+    fn dir(&self) -> Option<PathBuf> {
+        self.dir.lock().unwrap().clone()
+    }
+
+    pub fn policy(&self) -> RetentionPolicy {
+        self.policy.lock().unwrap().clone()
+    }
+
+    pub fn set_policy(&self, policy: RetentionPolicy) {
+        *self.policy.lock().unwrap() = policy;
+    }
+
+    pub fn save(&self, info: &CaptureInfo, mime_type: &str, bytes: &[u8]) -> Result<(), String> {
+        let Some(dir) = self.dir() else {
+            return Ok(());
+        };
+        if !valid_id(&info.capture_id) {
+            return Err(format!("Invalid capture id {}", info.capture_id));
+        }
+
+        let file = format!("{}.{}", info.capture_id, extension(mime_type));
+        std::fs::write(dir.join(&file), bytes).map_err(|e| e.to_string())?;
+
+        let record = CaptureRecord {
+            info: info.clone(),
+            mime_type: mime_type.to_string(),
+            file,
+            bytes: bytes.len() as u64,
+        };
+        let json = serde_json::to_string_pretty(&record).map_err(|e| e.to_string())?;
+        std::fs::write(dir.join(format!("{}.json", info.capture_id)), json).map_err(|e| e.to_string())
+    }
+
+    pub fn get(&self, capture_id: &str) -> Option<CaptureRecord> {
+        if !valid_id(capture_id) {
+            return None;
+        }
+        read_record(&self.dir()?.join(format!("{}.json", capture_id)))
+    }
+
+    pub fn read_image(&self, capture_id: &str) -> Option<(CaptureRecord, Vec<u8>)> {
+        let record = self.get(capture_id)?;
+        let bytes = std::fs::read(self.dir()?.join(&record.file)).ok()?;
+        Some((record, bytes))
+    }
+
+    // All records, newest first, optionally limited to one session
+    pub fn list(&self, session_id: Option<&str>) -> Vec<CaptureRecord> {
+        let Some(dir) = self.dir() else {
+            return Vec::new();
+        };
+
+        let mut records: Vec<CaptureRecord> = std::fs::read_dir(&dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                    .filter_map(|path| read_record(&path))
+                    .filter(|record| session_id.is_none() || record.info.session_id.as_deref() == session_id)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        records.sort_by_key(|record| std::cmp::Reverse(record.info.timestamp));
+        records
+    }
+
+    // Remove captures from disk; returns how many were deleted
+    pub fn delete(&self, capture_ids: &[String]) -> usize {
+        let Some(dir) = self.dir() else {
+            return 0;
+        };
+
+        capture_ids
+            .iter()
+            .filter_map(|id| self.get(id))
+            .filter(|record| {
+                let _ = std::fs::remove_file(dir.join(&record.file));
+                std::fs::remove_file(dir.join(format!("{}.json", record.info.capture_id))).is_ok()
+            })
+            .count()
+    }
+
+    // Enforce the retention policy; returns the ids of deleted captures
+    pub fn sweep(&self, now: u64) -> Vec<String> {
+        let policy = self.policy();
+        let mut records = self.list(None);
+        let mut expired = Vec::new();
+
+        if let Some(hours) = policy.max_age_hours {
+            let cutoff = now.saturating_sub(hours.saturating_mul(60 * 60 * 1000));
+            let (old, kept): (Vec<_>, Vec<_>) = records.into_iter().partition(|r| r.info.timestamp < cutoff);
+            expired.extend(old.into_iter().map(|r| r.info.capture_id));
+            records = kept;
+        }
+
+        if let Some(budget) = policy.max_total_bytes {
+            // Newest first, so everything past the budget is the oldest
+            let mut total = 0;
+            for record in records {
+                total += record.bytes;
+                if total > budget {
+                    expired.push(record.info.capture_id);
+                }
+            }
+        }
+
+        self.delete(&expired);
+        expired
+    }
+}
+
+fn read_record(path: &Path) -> Option<CaptureRecord> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+pub fn history_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(app.path().app_data_dir().map_err(|e| e.to_string())?.join(HISTORY_DIR))
+}
+
+pub fn load_policy(app: &tauri::AppHandle) -> RetentionPolicy {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .and_then(|dir| std::fs::read_to_string(dir.join(POLICY_FILE)).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_policy(app: &tauri::AppHandle, policy: &RetentionPolicy) -> Result<(), String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let json = serde_json::to_string_pretty(policy).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(POLICY_FILE), json).map_err(|e| e.to_string())
+}
+
+// Periodically delete captures that fall outside the retention policy
+pub fn spawn_sweeper(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(tokio::time::Duration::from_secs(SWEEP_INTERVAL_SECS));
+        loop {
+            ticker.tick().await;
+            let deleted = app.state::<crate::AppState>().history.sweep(capture::now_millis());
+            if !deleted.is_empty() {
+                println!("[Prism] Capture history sweep deleted {} capture(s)", deleted.len());
+            }
+        }
+    });
+}
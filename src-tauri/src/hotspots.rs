@@ -0,0 +1,286 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::async_runtime::JoinHandle;
+use tauri::{Emitter, Manager};
+
+use crate::capture::{Rect, ScreenInfo};
+use crate::cursor::inside;
+use crate::overlay::{box_to_desktop, point_to_desktop};
+use crate::shapes::ShapeKind;
+use crate::{exclusion, AppState, OverlayPayload};
+
+pub const TARGET_CLICKED_EVENT: &str = "target-clicked";
+pub const OFF_TARGET_EVENT: &str = "off-target-click";
+// Polling faster than this costs CPU without catching more clicks
+const MIN_INTERVAL_MS: u64 = 8;
+
+// Clickable geometry of one step target in logical desktop coordinates
+#[derive(Clone, Debug, PartialEq)]
+pub enum Hotspot {
+    Point(f64, f64),
+    Rect(Rect),
+    Polygon(Vec<(f64, f64)>),
+    // Arrows and polylines: clicks along the line count
+    Path(Vec<(f64, f64)>),
+}
+
+fn segment_distance(a: (f64, f64), b: (f64, f64), x: f64, y: f64) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq > 0.0 {
+        (((x - a.0) * dx + (y - a.1) * dy) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    ((a.0 + t * dx - x).powi(2) + (a.1 + t * dy - y).powi(2)).sqrt()
+}
+
+fn path_distance(path: &[(f64, f64)], closed: bool, x: f64, y: f64) -> f64 {
+    if path.len() == 1 {
+        return segment_distance(path[0], path[0], x, y);
+    }
+    let closing = if closed { path.last().zip(path.first()) } else { None };
+    path.windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .chain(closing.map(|(a, b)| (*a, *b)))
+        .map(|(a, b)| segment_distance(a, b, x, y))
+        .fold(f64::MAX, f64::min)
+}
+
+impl Hotspot {
+    // Logical pixels from the hotspot to a desktop point, 0 when inside it
+    pub fn distance(&self, x: f64, y: f64) -> f64 {
+        match self {
+            Hotspot::Point(px, py) => ((px - x).powi(2) + (py - y).powi(2)).sqrt(),
+            Hotspot::Rect(rect) => {
+                let dx = (rect.x - x).max(x - (rect.x + rect.width)).max(0.0);
+                let dy = (rect.y - y).max(y - (rect.y + rect.height)).max(0.0);
+                (dx * dx + dy * dy).sqrt()
+            }
+            Hotspot::Polygon(points) if inside(points, x, y) => 0.0,
+            Hotspot::Polygon(points) => path_distance(points, true, x, y),
+            Hotspot::Path(points) => path_distance(points, false, x, y),
+        }
+    }
+}
+
+// Targets of a step, in the order points, boxes, shapes. Text shapes explain
+// a target rather than being one and are skipped.
+pub fn from_payload(payload: &OverlayPayload, area: &Rect, screens: &[ScreenInfo]) -> Vec<Hotspot> {
+    let point = |p| point_to_desktop(p, area, screens);
+    let mut hotspots: Vec<Hotspot> = payload
+        .points
+        .iter()
+        .map(|p| {
+            let (x, y) = point(p);
+            Hotspot::Point(x, y)
+        })
+        .collect();
+    hotspots.extend(payload.boxes.iter().map(|b| Hotspot::Rect(box_to_desktop(b, area, screens))));
+
+    for shape in &payload.shapes {
+        let points: Vec<(f64, f64)> = shape.points().into_iter().map(point).collect();
+        let hotspot = match &shape.kind {
+            ShapeKind::Point { .. } | ShapeKind::Badge { .. } => Hotspot::Point(points[0].0, points[0].1),
+            ShapeKind::Box { bounds } => Hotspot::Rect(box_to_desktop(bounds, area, screens)),
+            ShapeKind::Polygon { .. } => Hotspot::Polygon(points),
+            ShapeKind::Arrow { .. } | ShapeKind::Polyline { .. } => Hotspot::Path(points),
+            ShapeKind::Text { .. } => continue,
+        };
+        hotspots.push(hotspot);
+    }
+
+    hotspots
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ClickListenerOptions {
+    // How often the mouse button is polled
+    pub interval_ms: u64,
+    // Logical pixels around a target that still count as hitting it
+    pub tolerance: f64,
+}
+
+impl Default for ClickListenerOptions {
+    fn default() -> Self {
+        Self { interval_ms: 16, tolerance: 16.0 }
+    }
+}
+
+// Click listener options passed to start_click_listener, defaults if omitted
+pub fn accept(options: Option<ClickListenerOptions>) -> Result<ClickListenerOptions, String> {
+    let options = options.unwrap_or_default();
+    if !(options.tolerance.is_finite() && options.tolerance >= 0.0) {
+        return Err("Click tolerance must be zero or more".to_string());
+    }
+    Ok(options)
+}
+
+// Payload of the target-clicked and off-target-click events
+#[derive(Clone, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetClick {
+    pub desktop_x: f64,
+    pub desktop_y: f64,
+    // Index of the hit (or, off target, the nearest) target among the step's
+    // points, boxes and shapes, in that order
+    pub target: Option<usize>,
+    pub distance: Option<f64>,
+    pub on_target: bool,
+}
+
+impl TargetClick {
+    pub fn new(hotspots: &[Hotspot], x: f64, y: f64, tolerance: f64) -> Self {
+        let nearest = hotspots
+            .iter()
+            .enumerate()
+            .map(|(index, hotspot)| (index, hotspot.distance(x, y)))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        Self {
+            desktop_x: x,
+            desktop_y: y,
+            target: nearest.map(|(index, _)| index),
+            distance: nearest.map(|(_, distance)| distance),
+            on_target: nearest.is_some_and(|(_, distance)| distance <= tolerance),
+        }
+    }
+
+    pub fn event(&self) -> &'static str {
+        if self.on_target {
+            TARGET_CLICKED_EVENT
+        } else {
+            OFF_TARGET_EVENT
+        }
+    }
+}
+
+// Turns polled button states into presses
+#[derive(Default)]
+struct PressDetector {
+    down: bool,
+}
+
+impl PressDetector {
+    fn update(&mut self, down: bool) -> bool {
+        let pressed = down && !self.down;
+        self.down = down;
+        pressed
+    }
+}
+
+// Background task that watches the mouse button and reports presses on or off
+// the current step's targets. The overlay stays click-through, so the click
+// also reaches the app underneath.
+pub struct ClickListener {
+    task: Mutex<Option<JoinHandle<()>>>,
+    hotspots: Arc<Mutex<Vec<Hotspot>>>,
+}
+
+impl ClickListener {
+    pub fn new() -> Self {
+        Self {
+            task: Mutex::new(None),
+            hotspots: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    // Called whenever the overlay shows a new step; no targets means no events
+    pub fn set_targets(&self, hotspots: Vec<Hotspot>) {
+        *self.hotspots.lock().unwrap() = hotspots;
+    }
+
+    pub fn start(&self, app: tauri::AppHandle, options: ClickListenerOptions) {
+        let task = tauri::async_runtime::spawn(run(app, options, self.hotspots.clone()));
+        if let Some(previous) = self.task.lock().unwrap().replace(task) {
+            previous.abort();
+        }
+    }
+
+    pub fn stop(&self) -> bool {
+        match self.task.lock().unwrap().take() {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+async fn run(app: tauri::AppHandle, options: ClickListenerOptions, hotspots: Arc<Mutex<Vec<Hotspot>>>) {
+    let interval = options.interval_ms.max(MIN_INTERVAL_MS);
+    let mut ticker = tokio::time::interval(tokio::time::Duration::from_millis(interval));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut presses = PressDetector::default();
+
+    loop {
+        ticker.tick().await;
+
+        let state = app.state::<AppState>();
+        let Some(down) = state.backend.primary_button_down() else {
+            continue;
+        };
+        if !presses.update(down) {
+            continue;
+        }
+        let Some((x, y)) = state.backend.cursor_position() else {
+            continue;
+        };
+
+        // Clicks on Prism's own panel (Proceed, chat) are neither
+        let on_panel = exclusion::visible_window_rects(&app)
+            .iter()
+            .any(|(label, rect)| !label.starts_with("screen-overlay") && rect.contains(x, y));
+        let hotspots = hotspots.lock().unwrap().clone();
+        if on_panel || hotspots.is_empty() {
+            continue;
+        }
+
+        let click = TargetClick::new(&hotspots, x, y, options.tolerance);
+        let _ = app.emit(click.event(), click);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_clicks_against_target_geometry() {
+        let hotspots = vec![
+            Hotspot::Point(100.0, 100.0),
+            Hotspot::Rect(Rect { x: 300.0, y: 100.0, width: 100.0, height: 50.0 }),
+            Hotspot::Polygon(vec![(500.0, 500.0), (600.0, 500.0), (550.0, 600.0)]),
+            Hotspot::Path(vec![(0.0, 400.0), (200.0, 400.0)]),
+        ];
+
+        let hit = |x, y| TargetClick::new(&hotspots, x, y, 16.0);
+
+        assert_eq!((hit(110.0, 105.0).target, hit(110.0, 105.0).on_target), (Some(0), true));
+        assert_eq!(hit(350.0, 120.0).distance, Some(0.0));
+        assert_eq!(hit(550.0, 530.0).target, Some(2));
+        assert!(hit(100.0, 410.0).on_target);
+
+        let miss = hit(250.0, 250.0);
+        assert!(!miss.on_target);
+        assert_eq!(miss.event(), OFF_TARGET_EVENT);
+        assert_eq!(hit(305.0, 95.0).event(), TARGET_CLICKED_EVENT);
+    }
+
+    #[test]
+    fn reports_each_press_once() {
+        let mut presses = PressDetector::default();
+        let pressed: Vec<bool> = [false, true, true, false, true].iter().map(|down| presses.update(*down)).collect();
+        assert_eq!(pressed, vec![false, true, false, false, true]);
+    }
+
+    #[test]
+    fn rejects_tolerances_that_would_miss_every_click() {
+        for tolerance in [f64::NAN, -1.0, f64::INFINITY] {
+            assert!(accept(Some(ClickListenerOptions { tolerance, ..Default::default() })).is_err());
+        }
+        assert_eq!(accept(None).unwrap().tolerance, 16.0);
+    }
+}
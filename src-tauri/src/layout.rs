@@ -0,0 +1,286 @@
+use serde::Serialize;
+
+use crate::capture::Rect;
+use crate::shapes::ShapeKind;
+use crate::OverlayPayload;
+
+// Label metrics of the overlay's text-sm, px-3 py-1 captions, in logical
+// pixels. Widths are estimated generously so real text fits inside.
+const CHAR_WIDTH: f64 = 8.0;
+const PADDING_X: f64 = 12.0;
+const LABEL_HEIGHT: f64 = 28.0;
+// Space between a label and its target, or another label
+const GAP: f64 = 6.0;
+// Labels pushed this many rings out before giving up on a clear spot
+const RINGS: usize = 3;
+// Point markers are drawn as circles of about this radius
+const POINT_RADIUS: f64 = 16.0;
+// Step panel in the top-left of the home overlay (fixed top-8 left-8 max-w-md)
+const PANEL: Rect = Rect { x: 32.0, y: 32.0, width: 448.0, height: 140.0 };
+
+#[derive(Clone, Copy, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum LabelTarget {
+    Point,
+    Box,
+    Shape,
+}
+
+// Line from a displaced label back to its target
+#[derive(Clone, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Leader {
+    pub x1: f64,
+    pub y1: f64,
+    pub x2: f64,
+    pub y2: f64,
+}
+
+// Where the overlay draws one caption or shape label, in logical pixels of
+// the overlay. `index` is the item's position in the payload's points, boxes
+// or shapes.
+#[derive(Clone, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Label {
+    pub target: LabelTarget,
+    pub index: usize,
+    pub text: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leader: Option<Leader>,
+}
+
+impl Label {
+    fn rect(&self) -> Rect {
+        Rect { x: self.x, y: self.y, width: self.width, height: self.height }
+    }
+}
+
+fn label_size(text: &str, max_width: f64) -> (f64, f64) {
+    let width = text.chars().count() as f64 * CHAR_WIDTH + 2.0 * PADDING_X;
+    (width.min(max_width), LABEL_HEIGHT)
+}
+
+fn overlap_area(a: &Rect, b: &Rect) -> f64 {
+    a.intersect(b).map_or(0.0, |rect| rect.width * rect.height)
+}
+
+// Logical pixels between two rectangles, 0 when they touch or overlap
+fn separation(a: &Rect, b: &Rect) -> f64 {
+    let dx = (a.x - (b.x + b.width)).max(b.x - (a.x + a.width)).max(0.0);
+    let dy = (a.y - (b.y + b.height)).max(b.y - (a.y + a.height)).max(0.0);
+    (dx * dx + dy * dy).sqrt()
+}
+
+fn closest(rect: &Rect, x: f64, y: f64) -> (f64, f64) {
+    (x.clamp(rect.x, rect.x + rect.width), y.clamp(rect.y, rect.y + rect.height))
+}
+
+// Spots for a `width` x `height` label around `target`, nearest first: above,
+// below, right and left, then the same again one label further out. Spots
+// above and below slide along the edge to stay on screen.
+fn candidates(target: &Rect, width: f64, height: f64, screen: &Rect) -> Vec<Rect> {
+    let (cx, cy) = target.center();
+    let slide = |x: f64| x.clamp(screen.x, (screen.x + screen.width - width).max(screen.x));
+    let mut spots = Vec::new();
+
+    for ring in 0..RINGS {
+        let out_y = GAP + ring as f64 * (height + GAP);
+        let out_x = GAP + ring as f64 * (width / 2.0 + GAP);
+        spots.push((slide(cx - width / 2.0), target.y - out_y - height));
+        spots.push((slide(cx - width / 2.0), target.y + target.height + out_y));
+        spots.push((target.x + target.width + out_x, cy - height / 2.0));
+        spots.push((target.x - out_x - width, cy - height / 2.0));
+    }
+
+    spots.into_iter().map(|(x, y)| Rect { x, y, width, height }).collect()
+}
+
+// Place labels one by one on the first spot that is on screen and clear of
+// every target, earlier label and the step panel. If there is none, the spot
+// overlapping least is used, pulled onto the screen. Labels that end up away
+// from their target get a leader line.
+fn place(items: Vec<(LabelTarget, usize, String, Rect)>, obstacles: &[Rect], screen: &Rect) -> Vec<Label> {
+    let mut labels: Vec<Label> = Vec::new();
+
+    for (target, index, text, anchor) in items {
+        let (width, height) = label_size(&text, screen.width);
+        let blocked = |spot: &Rect| -> f64 {
+            obstacles
+                .iter()
+                .chain(std::iter::once(&anchor))
+                .map(|rect| overlap_area(spot, rect))
+                .chain(labels.iter().map(|label| overlap_area(spot, &label.rect())))
+                .sum()
+        };
+        let on_screen = |spot: &Rect| spot.intersect(screen) == Some(*spot);
+
+        let spots = candidates(&anchor, width, height, screen);
+        let spot = spots
+            .iter()
+            .find(|spot| on_screen(spot) && blocked(spot) == 0.0)
+            .copied()
+            .unwrap_or_else(|| {
+                let pulled = spots.iter().map(|spot| Rect {
+                    x: spot.x.clamp(screen.x, (screen.x + screen.width - width).max(screen.x)),
+                    y: spot.y.clamp(screen.y, (screen.y + screen.height - height).max(screen.y)),
+                    ..*spot
+                });
+                pulled.min_by(|a, b| blocked(a).total_cmp(&blocked(b))).unwrap_or(spots[0])
+            });
+
+        let leader = (separation(&spot, &anchor) > GAP * 1.5).then(|| {
+            let (sx, sy) = spot.center();
+            let (x1, y1) = closest(&anchor, sx, sy);
+            let (x2, y2) = closest(&spot, x1, y1);
+            Leader { x1, y1, x2, y2 }
+        });
+
+        labels.push(Label { target, index, text, x: spot.x, y: spot.y, width, height, leader });
+    }
+
+    labels
+}
+
+// Lay out the caption of every point and box, and every shape's label, for a
+// payload already normalized to a `width` x `height` logical-pixel overlay
+pub fn labels(payload: &OverlayPayload, width: f64, height: f64) -> Vec<Label> {
+    let pixel = |x: f64, y: f64| (x * width, y * height);
+    let screen = Rect { x: 0.0, y: 0.0, width, height };
+    let mut targets: Vec<(LabelTarget, usize, Rect)> = Vec::new();
+
+    for (index, point) in payload.points.iter().enumerate() {
+        let (x, y) = pixel(point.x, point.y);
+        let rect = Rect { x: x - POINT_RADIUS, y: y - POINT_RADIUS, width: 2.0 * POINT_RADIUS, height: 2.0 * POINT_RADIUS };
+        targets.push((LabelTarget::Point, index, rect));
+    }
+    for (index, b) in payload.boxes.iter().enumerate() {
+        let (x0, y0) = pixel(b.x_min, b.y_min);
+        let (x1, y1) = pixel(b.x_max, b.y_max);
+        targets.push((LabelTarget::Box, index, Rect { x: x0, y: y0, width: x1 - x0, height: y1 - y0 }));
+    }
+    for (index, shape) in payload.shapes.iter().enumerate() {
+        let mut points: Vec<(f64, f64)> = shape.points().iter().map(|p| pixel(p.x, p.y)).collect();
+        if let ShapeKind::Box { bounds } = &shape.kind {
+            points = vec![pixel(bounds.x_min, bounds.y_min), pixel(bounds.x_max, bounds.y_max)];
+        }
+        let bounds = Rect::enclosing(&points);
+        let (x0, y0) = (bounds.x, bounds.y);
+        let rect = match &shape.kind {
+            // Text is its own label; its box only keeps others off it
+            ShapeKind::Text { text, .. } => {
+                let (width, height) = label_size(text, width);
+                Rect { x: x0, y: y0, width, height }
+            }
+            ShapeKind::Point { .. } | ShapeKind::Badge { .. } => Rect {
+                x: x0 - POINT_RADIUS,
+                y: y0 - POINT_RADIUS,
+                width: 2.0 * POINT_RADIUS,
+                height: 2.0 * POINT_RADIUS,
+            },
+            _ => bounds,
+        };
+        targets.push((LabelTarget::Shape, index, rect));
+    }
+
+    let mut obstacles: Vec<Rect> = targets.iter().map(|(_, _, rect)| *rect).collect();
+    let has_panel = payload.is_home != Some(false) && payload.walkthrough_steps.is_some_and(|steps| steps > 1);
+    if has_panel {
+        obstacles.push(PANEL);
+    }
+    if let Some(callout) = payload.magnifier.as_ref().and_then(|magnifier| magnifier.callout) {
+        obstacles.push(callout);
+    }
+
+    let items = targets
+        .into_iter()
+        .filter_map(|(target, index, rect)| {
+            let text = match target {
+                LabelTarget::Point | LabelTarget::Box => payload.caption.clone(),
+                LabelTarget::Shape => match &payload.shapes[index].kind {
+                    ShapeKind::Text { .. } => None,
+                    _ => payload.shapes[index].label.clone(),
+                },
+            };
+            text.filter(|text| !text.trim().is_empty()).map(|text| (target, index, text, rect))
+        })
+        .collect();
+
+    place(items, &obstacles, &screen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Rect = Rect { x: 0.0, y: 0.0, width: 1000.0, height: 800.0 };
+
+    fn item(index: usize, text: &str, x: f64, y: f64, width: f64, height: f64) -> (LabelTarget, usize, String, Rect) {
+        (LabelTarget::Box, index, text.to_string(), Rect { x, y, width, height })
+    }
+
+    #[test]
+    fn puts_labels_above_their_target_unless_that_is_off_screen() {
+        let labels = place(
+            vec![
+                item(0, "Save", 400.0, 400.0, 100.0, 40.0),
+                // Against the top edge: goes below instead of clipping
+                item(1, "Menu", 400.0, 0.0, 100.0, 20.0),
+                // Against the right edge: slides left but stays above
+                item(2, "Close window", 960.0, 200.0, 40.0, 40.0),
+            ],
+            &[],
+            &SCREEN,
+        );
+
+        let (width, height) = label_size("Save", SCREEN.width);
+        assert_eq!((labels[0].x, labels[0].y, labels[0].width), (450.0 - width / 2.0, 400.0 - GAP - height, width));
+        assert_eq!(labels[1].y, 20.0 + GAP);
+        assert_eq!(labels[2].x + labels[2].width, 1000.0);
+        assert!(labels[2].y < 200.0);
+        assert!(labels.iter().all(|label| label.leader.is_none()));
+    }
+
+    #[test]
+    fn moves_colliding_labels_apart_with_leaders() {
+        // Three boxes side by side whose labels would all land in the same
+        // spot, and an unlabelled control to the right of the last one
+        let items = vec![
+            item(0, "First option", 100.0, 300.0, 30.0, 30.0),
+            item(1, "Second option", 135.0, 300.0, 30.0, 30.0),
+            item(2, "Third option", 170.0, 300.0, 30.0, 30.0),
+        ];
+        let mut obstacles: Vec<Rect> = items.iter().map(|(_, _, _, rect)| *rect).collect();
+        obstacles.push(Rect { x: 206.0, y: 300.0, width: 100.0, height: 30.0 });
+
+        let labels = place(items, &obstacles, &SCREEN);
+
+        for (i, a) in labels.iter().enumerate() {
+            assert_eq!(a.rect().intersect(&SCREEN), Some(a.rect()));
+            for b in &labels[i + 1..] {
+                assert_eq!(overlap_area(&a.rect(), &b.rect()), 0.0, "{:?} overlaps {:?}", a, b);
+            }
+            for obstacle in &obstacles {
+                assert_eq!(overlap_area(&a.rect(), obstacle), 0.0);
+            }
+        }
+        assert_eq!(labels[0].y, 300.0 - GAP - LABEL_HEIGHT);
+        assert_eq!(labels[1].y, 330.0 + GAP);
+
+        // The third goes a ring out, above the first label, and points back
+        assert_eq!(labels[2].y, 300.0 - 2.0 * (GAP + LABEL_HEIGHT));
+        assert_eq!(labels[2].leader, Some(Leader { x1: 185.0, y1: 300.0, x2: 185.0, y2: 260.0 }));
+        assert!(labels[..2].iter().all(|label| label.leader.is_none()));
+    }
+
+    #[test]
+    fn overlaps_least_when_nothing_is_clear() {
+        // A target filling the whole screen leaves nowhere clear
+        let labels = place(vec![item(0, "Everything", 0.0, 0.0, 1000.0, 800.0)], &[], &SCREEN);
+        let rect = labels[0].rect();
+        assert_eq!(rect.intersect(&SCREEN), Some(rect));
+    }
+}
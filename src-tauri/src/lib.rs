@@ -1,29 +1,132 @@
-use screenshots::Screen;
-use base64::{engine::general_purpose::STANDARD, Engine as _};
+use screenshots::image::{imageops, RgbaImage};
 use tauri::{Emitter, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
 
+mod backend;
+mod capture;
+mod capture_store;
+mod change;
+mod clipboard;
+mod cursor;
+mod encode;
+mod exclusion;
+mod history;
+mod hotspots;
+mod layout;
+mod magnifier;
+mod overlay;
+mod redaction;
+mod render;
+mod selection;
+mod shapes;
+mod spotlight;
+mod theme;
+mod visibility;
+mod watcher;
+
+use backend::{CaptureBackend, WindowCapture};
+use capture::{CaptureInfo, CaptureResult, CaptureSource, NamedScreen, Rect, ScreenInfo, ScreenSelector};
+use capture_store::CaptureStore;
+use change::{ChangeReport, Fingerprint};
+use clipboard::ClipboardAccess;
+use cursor::{CursorStream, CursorStreamOptions};
+use encode::EncodeOptions;
+use exclusion::{ExcludedWindow, ExclusionMethod, ExclusionMode};
+use history::{CaptureHistory, HistoryEntry, RetentionPolicy};
+use overlay::{OverlaySnapshot, OverlayState};
+use hotspots::{ClickListener, ClickListenerOptions};
+use layout::Label;
+use magnifier::Magnifier;
+use redaction::RedactionRule;
+use selection::RegionCapture;
+use shapes::Shape;
+use spotlight::Spotlight;
+use theme::{OverlayStyle, OverlayTheme, StyleOverrides};
+use visibility::VisibilityOptions;
+use watcher::{ScreenWatcher, WatchOptions};
+
 // Focused Window State Management
 #[derive(Clone, Serialize, Deserialize, Debug)]
 struct FocusedWindowInfo {
     owner_name: String,
     window_name: String,
+    // On macOS a System Events process/index pair, not a window server id
     window_id: i64,
     process_id: i32,
+    // X11 window or CGWindowID, when known; only this one can be captured directly
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    native_id: Option<u32>,
+    // Window bounds in desktop points; zero width/height means unknown
+    #[serde(default)]
+    x: f64,
+    #[serde(default)]
+    y: f64,
+    #[serde(default)]
+    width: f64,
+    #[serde(default)]
+    height: f64,
+}
+
+impl FocusedWindowInfo {
+    fn frame(&self) -> Option<Rect> {
+        if self.width > 0.0 && self.height > 0.0 {
+            Some(Rect { x: self.x, y: self.y, width: self.width, height: self.height })
+        } else {
+            None
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn set_frame(&mut self, frame: Rect) {
+        self.x = frame.x;
+        self.y = frame.y;
+        self.width = frame.width;
+        self.height = frame.height;
+    }
 }
 
 struct AppState {
     focused_window: Mutex<Option<FocusedWindowInfo>>,
+    // When the focused window's frame was last read from the OS
+    #[cfg(target_os = "macos")]
+    focused_frame_read_at: Mutex<Option<std::time::Instant>>,
     selection_mode: Mutex<bool>,
+    captures: CaptureStore,
+    backend: Box<dyn CaptureBackend>,
+    watcher: ScreenWatcher,
+    cursor_stream: CursorStream,
+    click_listener: ClickListener,
+    redaction_rules: Mutex<Vec<RedactionRule>>,
+    overlay_theme: Mutex<OverlayTheme>,
+    history: CaptureHistory,
+    clipboard: ClipboardAccess,
+    overlay: Mutex<OverlayState>,
 }
 
 impl AppState {
     fn new() -> Self {
+        Self::with_backend(backend::from_env())
+    }
+
+    fn with_backend(backend: Box<dyn CaptureBackend>) -> Self {
+        println!("[Prism] Using {} capture backend", backend.name());
         Self {
             focused_window: Mutex::new(None),
+            #[cfg(target_os = "macos")]
+            focused_frame_read_at: Mutex::new(None),
             selection_mode: Mutex::new(false),
+            captures: CaptureStore::new(),
+            backend,
+            watcher: ScreenWatcher::new(),
+            cursor_stream: CursorStream::new(),
+            click_listener: ClickListener::new(),
+            redaction_rules: Mutex::new(Vec::new()),
+            overlay_theme: Mutex::new(OverlayTheme::default()),
+            history: CaptureHistory::new(),
+            clipboard: ClipboardAccess::new(),
+            overlay: Mutex::new(OverlayState::default()),
         }
     }
 
@@ -59,30 +162,14 @@ impl AppState {
     }
 }
 
-// Helper to wait for a window to emit a ready event
-async fn wait_for_window_ready(window: &WebviewWindow, event_name: &str) {
-    use tauri::Listener;
-
-    let (tx, rx) = tokio::sync::oneshot::channel();
-    let mut tx_option = Some(tx);
-
-    window.once(event_name, move |_event| {
-        if let Some(tx) = tx_option.take() {
-            let _ = tx.send(());
-        }
-    });
-
-    // Wait for ready signal with 5 second timeout
-    let _ = tokio::time::timeout(
-        tokio::time::Duration::from_secs(5),
-        rx
-    ).await;
-}
-
-#[derive(Clone, Serialize)]
+#[derive(Clone, Default, Serialize)]
 struct OverlayPayload {
+    // Shape protocol version, so overlays can tell what `shapes` may contain
+    version: u32,
+    // Legacy markers, still drawn alongside `shapes`
     points: Vec<Point>,
     boxes: Vec<BoundingBox>,
+    shapes: Vec<Shape>,
     #[serde(rename = "walkthroughSteps")]
     walkthrough_steps: Option<u32>,
     #[serde(rename = "currentStep")]
@@ -91,61 +178,826 @@ struct OverlayPayload {
     caption: Option<String>,
     #[serde(rename = "isComplete")]
     is_complete: Option<bool>,
+    // Colours and weights from the saved theme and this step's overrides
+    style: OverlayStyle,
+    // Dim mask around the step's targets, when turned on for this step
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spotlight: Option<Spotlight>,
+    // Auto-hide and reappear options, acted on by the backend
+    #[serde(skip_serializing_if = "Option::is_none")]
+    visibility: Option<VisibilityOptions>,
+    // Zoomed inset of the first target, when turned on for this step
+    #[serde(skip_serializing_if = "Option::is_none")]
+    magnifier: Option<Magnifier>,
+    // Caption and shape label positions, laid out per overlay by the backend
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    labels: Vec<Label>,
+    // Set per overlay window: the display it covers, the capture area and
+    // whether it shows the step panel
+    #[serde(rename = "screenBounds", skip_serializing_if = "Option::is_none")]
+    screen_bounds: Option<Rect>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    area: Option<Rect>,
+    #[serde(rename = "isHome", skip_serializing_if = "Option::is_none")]
+    is_home: Option<bool>,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+// The step fields every overlay and export command takes, validated, with
+// the style resolved against the saved theme
+#[allow(clippy::too_many_arguments)]
+fn step_payload(
+    state: &AppState,
+    points: Vec<Point>,
+    boxes: Vec<BoundingBox>,
+    walkthrough_steps: Option<u32>,
+    current_step: Option<u32>,
+    instruction: Option<String>,
+    caption: Option<String>,
+    is_complete: Option<bool>,
+    shapes: Option<Vec<Shape>>,
+    version: Option<u32>,
+    spotlight: Option<Spotlight>,
+    style: Option<StyleOverrides>,
+) -> Result<OverlayPayload, String> {
+    Ok(OverlayPayload {
+        version: shapes::PROTOCOL_VERSION,
+        points,
+        boxes,
+        shapes: shapes::accept(shapes, version)?,
+        walkthrough_steps,
+        current_step,
+        instruction,
+        caption,
+        is_complete,
+        style: state.overlay_theme.lock().unwrap().resolve(theme::accept(style)?.as_ref()),
+        spotlight: spotlight::accept(spotlight)?,
+        ..Default::default()
+    })
+}
+
+// Coordinates are normalized 0..1 to the capture area unless the item names a
+// screen (normalized to that display) or is in desktop coordinates
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 struct Point {
     x: f64,
     y: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    screen_id: Option<u32>,
+    // x/y are logical desktop pixels
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    desktop: bool,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 struct BoundingBox {
     x_min: f64,
     y_min: f64,
     x_max: f64,
     y_max: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    screen_id: Option<u32>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    desktop: bool,
 }
 
-#[tauri::command]
-async fn take_screenshot() -> Result<String, String> {
-    let screens = Screen::all().map_err(|e| e.to_string())?;
+// Reading window bounds spawns osascript, too slow for every watcher sample
+#[cfg(target_os = "macos")]
+const FOCUSED_FRAME_REFRESH: std::time::Duration = std::time::Duration::from_secs(2);
+
+// Current bounds of the focused window, re-read from the OS every so often so
+// captures follow the window even if the user moved or resized it
+fn focused_window_frame(state: &AppState) -> Option<Rect> {
+    let info = state.focused_window.lock().unwrap().clone()?;
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut read_at = state.focused_frame_read_at.lock().unwrap();
+        if read_at.map_or(true, |at| at.elapsed() >= FOCUSED_FRAME_REFRESH) {
+            *read_at = Some(std::time::Instant::now());
+            if let Ok(frame) = window_management::get_window_frame(&info) {
+                if let Some(stored) = state.focused_window.lock().unwrap().as_mut() {
+                    stored.set_frame(frame);
+                }
+                return Some(frame);
+            }
+        }
+    }
+
+    info.frame()
+}
+
+// Resolve which screen to capture and the logical desktop area on it.
+// With no explicit screen this is the focused window's bounds; an explicit
+// screen id or "primary" captures that whole screen.
+fn resolve_capture_area(
+    state: &AppState,
+    selector: Option<ScreenSelector>,
+) -> Result<(ScreenInfo, Rect), String> {
+    let screens = state.backend.screens()?;
 
     if screens.is_empty() {
         return Err("No screens found".to_string());
     }
 
-    let screen = &screens[0];
-    let screenshot = screen.capture().map_err(|e| e.to_string())?;
+    let window = match selector {
+        None | Some(ScreenSelector::Named(NamedScreen::Focused)) => focused_window_frame(state),
+        _ => None,
+    };
+
+    let screen = capture::resolve_screen(&screens, selector, window.map(|w| w.center()))?.clone();
+    let area = capture::capture_area(&screen, window);
+
+    Ok((screen, area))
+}
+
+// A full-screen grab before masking, cropping and encoding
+struct ScreenGrab {
+    screen: ScreenInfo,
+    area: Rect,
+    image: RgbaImage,
+    timestamp: u64,
+    // Pointer position in logical desktop coordinates, drawn into the capture when set
+    cursor: Option<(f64, f64)>,
+    source: CaptureSource,
+    window_capture_error: Option<String>,
+}
+
+// Screen showing a user-selected desktop rectangle and the part of it on that screen
+fn resolve_region(state: &AppState, region: Rect) -> Result<(ScreenInfo, Rect), String> {
+    let screens = state.backend.screens()?;
+    let screen = capture::resolve_screen(&screens, None, Some(region.center()))?.clone();
+    let area = region
+        .intersect(&screen.bounds())
+        .ok_or_else(|| "Region is not on any screen".to_string())?;
+
+    Ok((screen, area))
+}
+
+fn grab_screen(state: &AppState, selector: Option<ScreenSelector>) -> Result<ScreenGrab, String> {
+    let (screen, area) = resolve_capture_area(state, selector)?;
+    grab_area(state, screen, area)
+}
+
+fn grab_area(state: &AppState, screen: ScreenInfo, area: Rect) -> Result<ScreenGrab, String> {
+    let timestamp = capture::now_millis();
+    let image = state.backend.capture_screen(screen.id)?;
+
+    Ok(ScreenGrab {
+        screen,
+        area,
+        image,
+        timestamp,
+        cursor: None,
+        source: CaptureSource::Screen,
+        window_capture_error: None,
+    })
+}
+
+// Contents of one window straight from the window server, unaffected by
+// whatever is stacked on top of it
+// Default captures of the focused window read its own contents when its
+// native id is known and the backend can, so nothing covering it ends up in
+// the image. None means the screen is captured instead.
+fn grab_focused_window(state: &AppState, screen: &Option<ScreenSelector>, region: Option<Rect>) -> Option<Result<ScreenGrab, String>> {
+    if screen.is_some() || region.is_some() {
+        return None;
+    }
+    let native_id = state.focused_window.lock().unwrap().as_ref()?.native_id?;
+    Some(grab_window(state, native_id))
+}
+
+fn grab_window(state: &AppState, window_id: u32) -> Result<ScreenGrab, String> {
+    let timestamp = capture::now_millis();
+    let WindowCapture { image, frame } = state.backend.capture_window(window_id)?;
+    let screens = state.backend.screens()?;
+    let screen = capture::resolve_screen(&screens, None, Some(frame.center()))?;
+
+    // The window image stands in for a screen covering exactly the window, so
+    // redaction, cursor and crop math keep working in desktop coordinates
+    let window_screen = ScreenInfo {
+        id: screen.id,
+        x: frame.x.round() as i32,
+        y: frame.y.round() as i32,
+        width: frame.width.round() as u32,
+        height: frame.height.round() as u32,
+        scale_factor: (image.width() as f64 / frame.width.max(1.0)) as f32,
+        is_primary: screen.is_primary,
+    };
+
+    Ok(ScreenGrab {
+        screen: window_screen,
+        area: frame,
+        image,
+        timestamp,
+        cursor: None,
+        source: CaptureSource::Window,
+        window_capture_error: None,
+    })
+}
+
+// Frames of the open windows of the given apps, for app redaction rules
+#[cfg(target_os = "macos")]
+fn app_window_frames(app_names: &[String]) -> Result<Vec<(String, Rect)>, String> {
+    if app_names.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    Ok(window_management::get_all_windows()?
+        .into_iter()
+        .filter(|window| app_names.iter().any(|name| name.eq_ignore_ascii_case(&window.owner_name)))
+        .filter_map(|window| Some((window.owner_name.clone(), window.frame()?)))
+        .collect())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn app_window_frames(app_names: &[String]) -> Result<Vec<(String, Rect)>, String> {
+    if app_names.is_empty() {
+        Ok(Vec::new())
+    } else {
+        Err("App redaction rules are only supported on macOS".to_string())
+    }
+}
+
+// Apply redaction rules, mask the given desktop rectangles, crop to the capture
+// area, encode and store in memory and in the capture history. `app_windows`
+// are the window frames of apps named in app redaction rules.
+fn finish_capture(
+    state: &AppState,
+    grab: ScreenGrab,
+    session_id: Option<String>,
+    app_windows: &[(String, Rect)],
+    masks: Vec<(String, Rect)>,
+    mut excluded_windows: Vec<ExcludedWindow>,
+    encoding: &EncodeOptions,
+) -> Result<CaptureResult, String> {
+    let ScreenGrab { screen, area, mut image, timestamp, cursor, source, window_capture_error } = grab;
+
+    // Redact before anything is cropped or encoded so sensitive pixels never leave this function.
+    // Rules describe this desktop, so imported images are left alone.
+    let redactions = if source.is_desktop() {
+        redaction::resolve(&state.redaction_rules.lock().unwrap(), &area, app_windows)
+    } else {
+        Vec::new()
+    };
+    redaction::apply(&mut image, &screen, &redactions);
+
+    for (label, rect) in masks {
+        if capture::mask_area(&mut image, &rect, &screen) {
+            excluded_windows.push(ExcludedWindow { label, method: ExclusionMethod::Masked });
+        }
+    }
+
+    // Drawn last so the pointer stays visible over masked Prism windows
+    if let Some((x, y)) = cursor {
+        cursor::draw_cursor(&mut image, &screen, x, y);
+    }
+
+    // Crop to the capture area, converting points to pixels for HiDPI screens
+    let crop = capture::physical_crop(&area, &screen, image.width(), image.height());
+    let cropped = imageops::crop_imm(&image, crop.x, crop.y, crop.width, crop.height).to_image();
+
+    let (bytes, encoding) = encode::encode(&cropped, encoding)?;
+    let fingerprint = Fingerprint::of(&cropped);
+
+    let info = CaptureInfo {
+        capture_id: capture::next_capture_id(timestamp),
+        session_id,
+        screen_id: screen.id,
+        crop,
+        crop_logical: area,
+        scale_factor: image.width() as f64 / screen.width.max(1) as f64,
+        timestamp,
+        focused_window: if source.is_desktop() {
+            state.focused_window.lock().unwrap().clone()
+        } else {
+            None
+        },
+        encoding,
+        excluded_windows,
+        redactions,
+        cursor: cursor.map(|(x, y)| cursor::normalize(&area, x, y)),
+        source,
+        window_capture_error,
+    };
+    let mime_type = info.encoding.format.mime_type();
+    if let Err(e) = state.history.save(&info, mime_type, &bytes) {
+        println!("[Prism] Failed to save capture {} to history: {}", info.capture_id, e);
+    }
+    state.captures.insert(info.clone(), mime_type, bytes, fingerprint);
+
+    Ok(CaptureResult {
+        url: capture_store::capture_url(&info.capture_id),
+        info,
+    })
+}
+
+#[tauri::command]
+async fn list_screens(state: tauri::State<'_, AppState>) -> Result<Vec<ScreenInfo>, String> {
+    state.backend.screens()
+}
+
+// `region` is a logical desktop rectangle, e.g. from start_region_selection,
+// captured instead of the focused window
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn take_screenshot(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    screen: Option<ScreenSelector>,
+    region: Option<Rect>,
+    encoding: Option<EncodeOptions>,
+    exclude: Option<ExclusionMode>,
+    session_id: Option<String>,
+    include_cursor: Option<bool>,
+) -> Result<CaptureResult, String> {
+    screenshot(&app, &state, screen, region, encoding, exclude, session_id, include_cursor).await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn screenshot(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    screen: Option<ScreenSelector>,
+    region: Option<Rect>,
+    encoding: Option<EncodeOptions>,
+    exclude: Option<ExclusionMode>,
+    session_id: Option<String>,
+    include_cursor: Option<bool>,
+) -> Result<CaptureResult, String> {
+    let exclude = exclude.unwrap_or_default();
+
+    // Refuse to capture rather than send windows we were told to redact
+    let app_names = redaction::app_names(&state.redaction_rules.lock().unwrap());
+    let app_windows = app_window_frames(&app_names)
+        .map_err(|e| format!("Could not apply app redaction rules: {}", e))?;
+
+    let window_capture_error = match grab_focused_window(state, &screen, region) {
+        Some(Ok(mut grab)) => {
+            if include_cursor.unwrap_or(false) {
+                grab.cursor = state.backend.cursor_position();
+            }
+            return finish_capture(state, grab, session_id, &app_windows, Vec::new(), Vec::new(), &encoding.unwrap_or_default());
+        }
+        Some(Err(e)) => Some(e),
+        None => None,
+    };
+
+    // Keep Prism's own windows out of the image
+    let hidden = match exclude {
+        ExclusionMode::Hide => exclusion::hide_windows(app).await,
+        _ => Vec::new(),
+    };
+    let to_mask = match exclude {
+        ExclusionMode::None => Vec::new(),
+        _ => exclusion::visible_window_rects(app),
+    };
+
+    let grab = match region {
+        Some(region) => resolve_region(state, region).and_then(|(screen, area)| grab_area(state, screen, area)),
+        None => grab_screen(state, screen),
+    };
+    let grab = grab.map(|mut grab| {
+        // Screen capture APIs leave the pointer out; composite it ourselves when asked
+        if include_cursor.unwrap_or(false) {
+            grab.cursor = state.backend.cursor_position();
+        }
+        grab.window_capture_error = window_capture_error;
+        grab
+    });
+    exclusion::restore_windows(&hidden);
+
+    let excluded_windows = hidden
+        .iter()
+        .map(|window| ExcludedWindow {
+            label: window.label().to_string(),
+            method: ExclusionMethod::Hidden,
+        })
+        .collect();
+
+    // Encode (PNG at full resolution unless options say otherwise)
+    finish_capture(state, grab?, session_id, &app_windows, to_mask, excluded_windows, &encoding.unwrap_or_default())
+}
+
+// Raw encoded bytes of a stored capture, for uploading to the model
+#[tauri::command]
+async fn get_capture_bytes(
+    state: tauri::State<'_, AppState>,
+    capture_id: String,
+) -> Result<tauri::ipc::Response, String> {
+    load_capture(&state, &capture_id)
+        .map(|(_, _, bytes)| tauri::ipc::Response::new(bytes))
+        .ok_or_else(|| format!("Capture {} not found", capture_id))
+}
+
+// Metadata, MIME type and encoded bytes of a capture. Recent captures are in
+// memory; older ones are only in the on-disk history.
+fn load_capture(state: &AppState, capture_id: &str) -> Option<(CaptureInfo, String, Vec<u8>)> {
+    match state.captures.get(capture_id) {
+        Some(capture) => Some((capture.info, capture.mime_type.to_string(), capture.bytes.as_ref().clone())),
+        None => state
+            .history
+            .read_image(capture_id)
+            .map(|(record, bytes)| (record.info, record.mime_type, bytes)),
+    }
+}
+
+// A capture's fingerprint and area. Captures only left in history are
+// fingerprinted from their stored image.
+fn capture_fingerprint(state: &AppState, capture_id: &str) -> Result<(Fingerprint, Rect), String> {
+    if let Some(capture) = state.captures.get(capture_id) {
+        return Ok((capture.fingerprint, capture.info.crop_logical));
+    }
+    let (record, bytes) = state
+        .history
+        .read_image(capture_id)
+        .ok_or_else(|| format!("Capture {} not found", capture_id))?;
+    let image = screenshots::image::load_from_memory(&bytes)
+        .map_err(|e| e.to_string())?
+        .to_rgba8();
+    Ok((Fingerprint::of(&image), record.info.crop_logical))
+}
+
+// Store an image that did not come from the screen as if it were a capture of
+// a screen of the same size, so model coordinates map onto its pixels
+fn import_image(
+    state: &AppState,
+    image: RgbaImage,
+    source: CaptureSource,
+    session_id: Option<String>,
+    encoding: &EncodeOptions,
+) -> Result<CaptureResult, String> {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return Err("Image is empty".to_string());
+    }
+
+    let screen = ScreenInfo {
+        id: 0,
+        x: 0,
+        y: 0,
+        width,
+        height,
+        scale_factor: 1.0,
+        is_primary: true,
+    };
+    let grab = ScreenGrab {
+        area: screen.bounds(),
+        screen,
+        image,
+        timestamp: capture::now_millis(),
+        cursor: None,
+        source,
+        window_capture_error: None,
+    };
+
+    finish_capture(state, grab, session_id, &[], Vec::new(), Vec::new(), encoding)
+}
+
+// Load an image file (PNG, JPEG, ...) into the capture store, e.g. a screenshot
+// sent in by a customer, so point/detect/walkthrough can run on it
+#[tauri::command]
+async fn load_image_file(
+    state: tauri::State<'_, AppState>,
+    path: String,
+    encoding: Option<EncodeOptions>,
+    session_id: Option<String>,
+) -> Result<CaptureResult, String> {
+    let image = screenshots::image::open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path, e))?
+        .to_rgba8();
+
+    import_image(&state, image, CaptureSource::File, session_id, &encoding.unwrap_or_default())
+}
+
+#[tauri::command]
+async fn load_clipboard_image(
+    state: tauri::State<'_, AppState>,
+    encoding: Option<EncodeOptions>,
+    session_id: Option<String>,
+) -> Result<CaptureResult, String> {
+    let image = state.clipboard.read_image()?;
+    import_image(&state, image, CaptureSource::Clipboard, session_id, &encoding.unwrap_or_default())
+}
+
+// Draw the overlay annotations onto a capture
+fn render_annotated(state: &AppState, capture_id: &str, payload: &OverlayPayload) -> Result<RgbaImage, String> {
+    let (info, _, bytes) = load_capture(state, capture_id)
+        .ok_or_else(|| format!("Capture {} not found", capture_id))?;
+    let mut image = screenshots::image::load_from_memory(&bytes)
+        .map_err(|e| e.to_string())?
+        .to_rgba8();
+
+    // Items in desktop pixels or on a named screen are drawn where they are on
+    // the desktop, which only a desktop capture shows
+    let mapped;
+    let payload = if overlay::has_absolute_items(payload) {
+        if !info.source.is_desktop() {
+            return Err("Desktop and per-screen coordinates need a desktop capture".to_string());
+        }
+        let screens = state.backend.screens()?;
+        mapped = overlay::normalize_to(payload, &info.crop_logical, &screens, &info.crop_logical);
+        &mapped
+    } else {
+        payload
+    };
+
+    // Encoding may have downscaled the capture; markers keep their on-screen size
+    let scale = image.width() as f64 / info.crop_logical.width.max(1.0);
+    render::annotate(&mut image, payload, scale);
+
+    Ok(image)
+}
+
+fn render_annotated_png(state: &AppState, capture_id: &str, payload: &OverlayPayload) -> Result<Vec<u8>, String> {
+    let image = render_annotated(state, capture_id, payload)?;
+    encode::encode(&image, &EncodeOptions::default()).map(|(png, _)| png)
+}
+
+// PNG of a capture with the same points, boxes, caption and step panel the
+// overlay shows, for exporting steps to tickets and docs
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn render_annotated_capture(
+    state: tauri::State<'_, AppState>,
+    capture_id: String,
+    points: Vec<Point>,
+    boxes: Vec<BoundingBox>,
+    walkthrough_steps: Option<u32>,
+    current_step: Option<u32>,
+    instruction: Option<String>,
+    caption: Option<String>,
+    is_complete: Option<bool>,
+    shapes: Option<Vec<Shape>>,
+    version: Option<u32>,
+    spotlight: Option<Spotlight>,
+    style: Option<StyleOverrides>,
+) -> Result<tauri::ipc::Response, String> {
+    let payload = step_payload(
+        &state,
+        points,
+        boxes,
+        walkthrough_steps,
+        current_step,
+        instruction,
+        caption,
+        is_complete,
+        shapes,
+        version,
+        spotlight,
+        style,
+    )?;
+
+    render_annotated_png(&state, &capture_id, &payload).map(tauri::ipc::Response::new)
+}
+
+// Same rendering as render_annotated_capture, placed on the system clipboard
+// for pasting into tickets and chats
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn copy_annotated_capture(
+    state: tauri::State<'_, AppState>,
+    capture_id: String,
+    points: Vec<Point>,
+    boxes: Vec<BoundingBox>,
+    walkthrough_steps: Option<u32>,
+    current_step: Option<u32>,
+    instruction: Option<String>,
+    caption: Option<String>,
+    is_complete: Option<bool>,
+    shapes: Option<Vec<Shape>>,
+    version: Option<u32>,
+    spotlight: Option<Spotlight>,
+    style: Option<StyleOverrides>,
+) -> Result<(), String> {
+    let payload = step_payload(
+        &state,
+        points,
+        boxes,
+        walkthrough_steps,
+        current_step,
+        instruction,
+        caption,
+        is_complete,
+        shapes,
+        version,
+        spotlight,
+        style,
+    )?;
+
+    let image = render_annotated(&state, &capture_id, &payload)?;
+    state.clipboard.write_image(&image)
+}
+
+// Capture history, newest first, optionally for one chat or walkthrough session
+#[tauri::command]
+async fn list_captures(
+    state: tauri::State<'_, AppState>,
+    session_id: Option<String>,
+) -> Result<Vec<HistoryEntry>, String> {
+    Ok(state
+        .history
+        .list(session_id.as_deref())
+        .into_iter()
+        .map(HistoryEntry::from)
+        .collect())
+}
+
+#[tauri::command]
+async fn get_capture(
+    state: tauri::State<'_, AppState>,
+    capture_id: String,
+) -> Result<HistoryEntry, String> {
+    state
+        .history
+        .get(&capture_id)
+        .map(HistoryEntry::from)
+        .ok_or_else(|| format!("Capture {} not found", capture_id))
+}
+
+// Delete the given captures, or every capture of a session. Returns how many
+// were removed from disk.
+#[tauri::command]
+async fn delete_captures(
+    state: tauri::State<'_, AppState>,
+    capture_ids: Option<Vec<String>>,
+    session_id: Option<String>,
+) -> Result<usize, String> {
+    let mut ids = capture_ids.unwrap_or_default();
+    if let Some(session_id) = session_id {
+        ids.extend(
+            state
+                .history
+                .list(Some(&session_id))
+                .into_iter()
+                .map(|record| record.info.capture_id),
+        );
+    }
+
+    state.captures.remove(&ids);
+    Ok(state.history.delete(&ids))
+}
+
+#[tauri::command]
+async fn get_retention_policy(state: tauri::State<'_, AppState>) -> Result<RetentionPolicy, String> {
+    Ok(state.history.policy())
+}
+
+// Persist a new retention policy and apply it right away
+#[tauri::command]
+async fn set_retention_policy(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    policy: RetentionPolicy,
+) -> Result<(), String> {
+    history::save_policy(&app, &policy)?;
+    state.history.set_policy(policy);
+    state.history.sweep(capture::now_millis());
+    Ok(())
+}
+
+// How much the screen changed between two stored captures. `threshold` is the
+// mean brightness change (0-255) a grid cell needs to count as changed.
+#[tauri::command]
+async fn compare_captures(
+    state: tauri::State<'_, AppState>,
+    before_id: String,
+    after_id: String,
+    threshold: Option<u8>,
+) -> Result<ChangeReport, String> {
+    let (before, _) = capture_fingerprint(&state, &before_id)?;
+    let (after, area) = capture_fingerprint(&state, &after_id)?;
+
+    Ok(change::compare(&before, &after, threshold.unwrap_or(change::DEFAULT_THRESHOLD), &area))
+}
+
+#[tauri::command]
+async fn get_redaction_rules(state: tauri::State<'_, AppState>) -> Result<Vec<RedactionRule>, String> {
+    Ok(state.redaction_rules.lock().unwrap().clone())
+}
+
+// Replace the redaction rule set and persist it in the app data dir
+#[tauri::command]
+async fn set_redaction_rules(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    rules: Vec<RedactionRule>,
+) -> Result<(), String> {
+    redaction::validate(&rules)?;
+    redaction::save(&app, &rules)?;
+    *state.redaction_rules.lock().unwrap() = rules;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_overlay_theme(state: tauri::State<'_, AppState>) -> Result<OverlayTheme, String> {
+    Ok(state.overlay_theme.lock().unwrap().clone())
+}
+
+// Persist the overlay preset and overrides; used from the next step shown or
+// exported
+#[tauri::command]
+async fn set_overlay_theme(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    theme: OverlayTheme,
+) -> Result<(), String> {
+    theme::validate(&theme.overrides)?;
+    theme::save(&app, &theme)?;
+    *state.overlay_theme.lock().unwrap() = theme;
+    Ok(())
+}
+
+// Start sampling the screen in the background; emits screen-changed with the
+// change score and bounding box whenever the watched area changes
+#[tauri::command]
+async fn start_screen_watcher(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    options: Option<WatchOptions>,
+) -> Result<(), String> {
+    state.watcher.start(app, options.unwrap_or_default());
+    Ok(())
+}
+
+#[tauri::command]
+async fn stop_screen_watcher(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.watcher.stop())
+}
+
+// Poll the pointer and emit throttled cursor-moved events, normalized to the
+// given capture's area (or the current capture area) like model points
+#[tauri::command]
+async fn start_cursor_stream(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    options: Option<CursorStreamOptions>,
+) -> Result<(), String> {
+    let options = options.unwrap_or_default();
+    if state.backend.cursor_position().is_none() {
+        return Err(format!("The {} backend cannot read the cursor position", state.backend.name()));
+    }
+
+    let area = match &options.capture_id {
+        Some(id) => load_capture(&state, id)
+            .map(|(info, _, _)| info.crop_logical)
+            .ok_or_else(|| format!("Capture {} not found", id))?,
+        None => resolve_capture_area(&state, None)?.1,
+    };
 
-    // Calculate left 3/4 of screen width
-    let screen_width = screenshot.width();
-    let screen_height = screenshot.height();
-    let crop_width = (screen_width as f64 * 0.75) as u32;
+    state.cursor_stream.start(app, options, area);
+    Ok(())
+}
 
-    // Crop to left 3/4 of screen
-    use screenshots::image::imageops;
-    let cropped = imageops::crop_imm(
-        &screenshot,
-        0,              // x position
-        0,              // y position
-        crop_width,     // width (75% of screen)
-        screen_height   // height (full height)
-    );
+#[tauri::command]
+async fn stop_cursor_stream(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.cursor_stream.stop())
+}
 
-    // Encode to PNG bytes
-    let mut bytes: Vec<u8> = Vec::new();
-    let mut cursor = std::io::Cursor::new(&mut bytes);
-    cropped
-        .to_image()
-        .write_to(&mut cursor, screenshots::image::ImageFormat::Png)
-        .map_err(|e| e.to_string())?;
+// Emit target-clicked / off-target-click when the user clicks while an overlay
+// shows a step, checked against that step's points, boxes and shapes
+#[tauri::command]
+async fn start_click_listener(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    options: Option<ClickListenerOptions>,
+) -> Result<(), String> {
+    let options = hotspots::accept(options)?;
+    if state.backend.primary_button_down().is_none() {
+        return Err(format!("The {} backend cannot read the mouse buttons", state.backend.name()));
+    }
 
-    let base64 = STANDARD.encode(&bytes);
+    state.click_listener.start(app, options);
+    Ok(())
+}
 
-    Ok(format!("data:image/png;base64,{}", base64))
+#[tauri::command]
+async fn stop_click_listener(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.click_listener.stop())
+}
+
+// Serves prism-capture://localhost/<capture_id> from the capture store or history
+fn capture_protocol_response(
+    app: &tauri::AppHandle,
+    request: &tauri::http::Request<Vec<u8>>,
+) -> tauri::http::Response<Vec<u8>> {
+    let capture_id = request.uri().path().trim_start_matches('/');
+    let state = app.state::<AppState>();
+
+    match load_capture(&state, capture_id) {
+        Some((_, mime_type, bytes)) => tauri::http::Response::builder()
+            .status(200)
+            .header("Content-Type", mime_type)
+            .header("Access-Control-Allow-Origin", "*")
+            .body(bytes)
+            .unwrap_or_default(),
+        None => tauri::http::Response::builder()
+            .status(404)
+            .header("Access-Control-Allow-Origin", "*")
+            .body(Vec::new())
+            .unwrap_or_default(),
+    }
 }
 
 #[tauri::command]
@@ -174,9 +1026,33 @@ async fn open_settings_window(app: tauri::AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+// Create a transparent overlay window over a logical desktop area
+fn build_overlay_window(app: &tauri::AppHandle, label: &str, page: &str, area: Rect) -> Result<WebviewWindow, String> {
+    WebviewWindowBuilder::new(
+        app,
+        label,
+        WebviewUrl::App(page.into())
+    )
+    .title("Screen Overlay")
+    .inner_size(area.width, area.height)
+    .position(area.x, area.y)
+    .decorations(false)
+    .transparent(true)
+    .always_on_top(true)
+    // Keep the overlay out of screen captures (macOS and Windows) so the
+    // watcher and take_screenshot see the app underneath
+    .content_protected(true)
+    .build()
+    .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn open_screen_overlay(
     app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    screen: Option<ScreenSelector>,
+    capture_id: Option<String>,
     points: Vec<Point>,
     boxes: Vec<BoundingBox>,
     walkthrough_steps: Option<u32>,
@@ -184,42 +1060,18 @@ async fn open_screen_overlay(
     instruction: Option<String>,
     caption: Option<String>,
     is_complete: Option<bool>,
+    shapes: Option<Vec<Shape>>,
+    version: Option<u32>,
+    spotlight: Option<Spotlight>,
+    visibility: Option<VisibilityOptions>,
+    magnifier: Option<Magnifier>,
+    style: Option<StyleOverrides>,
 ) -> Result<(), String> {
-
-    // Close existing overlay window if any
-    if let Some(window) = app.get_webview_window("screen-overlay") {
-        let _ = window.close();
-    }
-
-    // Get screen dimensions
-    let screens = Screen::all().map_err(|e| e.to_string())?;
-    let screen = screens.get(0).ok_or("No screen found")?;
-
-    let screen_width = screen.display_info.width as f64;
-    let screen_height = screen.display_info.height as f64;
-
-    // Overlay should cover left 3/4 of screen (matching screenshot dimensions)
-    let overlay_width = screen_width * 0.75;
-
-    // Create transparent overlay window covering left 3/4 of screen
-    let window = WebviewWindowBuilder::new(
-        &app,
-        "screen-overlay",
-        WebviewUrl::App("overlay.html".into())
-    )
-    .title("Screen Overlay")
-    .inner_size(overlay_width, screen_height)
-    .position(0.0, 0.0)
-    .decorations(false)
-    .transparent(true)
-    .always_on_top(true)
-    .build()
-    .map_err(|e| e.to_string())?;
-
-    // Make window click-through (ignore cursor events)
-    let _ = window.set_ignore_cursor_events(true);
-
-    let payload = OverlayPayload {
+    let mut payload = OverlayPayload {
+        visibility: visibility::accept(visibility)?,
+        magnifier: magnifier::accept(magnifier)?,
+        ..step_payload(
+        &state,
         points,
         boxes,
         walkthrough_steps,
@@ -227,21 +1079,58 @@ async fn open_screen_overlay(
         instruction,
         caption,
         is_complete,
+        shapes,
+        version,
+        spotlight,
+        style,
+    )?
     };
 
-    // Wait for window to be ready, then send data
-    let window_clone = window.clone();
-    tokio::spawn(async move {
-        wait_for_window_ready(&window_clone, "overlay-ready").await;
-        let _ = window_clone.emit("overlay-data", payload);
-    });
+    // Close existing overlay windows if any
+    close_overlays(&app);
+
+    // Normalized items refer to the area of the capture they were found in,
+    // or the area take_screenshot would capture right now.
+    // Imported images have no place on the desktop; fall back to the current capture area
+    let area = match capture_id
+        .as_deref()
+        .and_then(|id| state.captures.info(id))
+        .filter(|info| info.source.is_desktop())
+    {
+        Some(info) => info.crop_logical,
+        None => resolve_capture_area(&state, screen)?.1,
+    };
 
+    let screens = state.backend.screens()?;
+    magnifier::prepare(&state, &mut payload, &area, &screens, capture_id.as_deref());
+    state.click_listener.set_targets(hotspots::from_payload(&payload, &area, &screens));
+    state.overlay.lock().unwrap().set_step(area, payload);
+
+    // Each overlay gets its part of the step once its page reports ready
+    build_overlay_windows(&app, &screens)?;
+    visibility::set_visible(&app, true);
+    Ok(())
+}
+
+// One transparent, click-through overlay per display
+fn build_overlay_windows(app: &tauri::AppHandle, screens: &[ScreenInfo]) -> Result<(), String> {
+    for screen in screens {
+        let window = build_overlay_window(app, &overlay::label(screen.id), "overlay.html", screen.bounds())?;
+        let _ = window.set_ignore_cursor_events(true);
+    }
     Ok(())
 }
 
+// Route new step data to the open overlays. `capture_id` re-anchors normalized
+// items to a newer capture, e.g. the one the step was found in. With no
+// overlays open (closed, or open_screen_overlay still running) the step is
+// kept and shown once the overlays are ready, reopening them if needed.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn update_screen_overlay_data(
     app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    capture_id: Option<String>,
     points: Vec<Point>,
     boxes: Vec<BoundingBox>,
     walkthrough_steps: Option<u32>,
@@ -249,31 +1138,117 @@ async fn update_screen_overlay_data(
     instruction: Option<String>,
     caption: Option<String>,
     is_complete: Option<bool>,
+    shapes: Option<Vec<Shape>>,
+    version: Option<u32>,
+    spotlight: Option<Spotlight>,
+    visibility: Option<VisibilityOptions>,
+    magnifier: Option<Magnifier>,
+    style: Option<StyleOverrides>,
 ) -> Result<(), String> {
-    if let Some(window) = app.get_webview_window("screen-overlay") {
-        let payload = OverlayPayload {
-            points,
-            boxes,
-            walkthrough_steps,
-            current_step,
-            instruction,
-            caption,
-            is_complete,
-        };
-        window.emit("overlay-data", payload)
-            .map_err(|e| format!("Failed to emit overlay-data: {:?}", e))?;
+    let mut payload = OverlayPayload {
+        visibility: visibility::accept(visibility)?,
+        magnifier: magnifier::accept(magnifier)?,
+        ..step_payload(
+        &state,
+        points,
+        boxes,
+        walkthrough_steps,
+        current_step,
+        instruction,
+        caption,
+        is_complete,
+        shapes,
+        version,
+        spotlight,
+        style,
+    )?
+    };
 
-        Ok(())
-    } else {
-        Err("No overlay window exists. Use open_screen_overlay first.".to_string())
+    let anchored = capture_id
+        .as_deref()
+        .and_then(|id| state.captures.info(id))
+        .filter(|info| info.source.is_desktop())
+        .map(|info| info.crop_logical);
+    let area = match anchored.or(state.overlay.lock().unwrap().area) {
+        Some(area) => area,
+        None => resolve_capture_area(&state, None)?.1,
+    };
+
+    let screens = state.backend.screens()?;
+    magnifier::prepare(&state, &mut payload, &area, &screens, capture_id.as_deref());
+    let routed = overlay::route(&payload, &area, &screens);
+    state.click_listener.set_targets(hotspots::from_payload(&payload, &area, &screens));
+    state.overlay.lock().unwrap().set_step(area, payload);
+
+    let windows = overlay::windows(&app);
+    if windows.is_empty() {
+        build_overlay_windows(&app, &screens)?;
     }
+
+    // Overlays still loading pick the step up when they report ready
+    for (screen, payload) in routed {
+        if windows.iter().any(|(id, _)| *id == screen.id) {
+            overlay::send(&app, screen.id, &payload)?;
+        }
+    }
+    visibility::set_visible(&app, true);
+
+    Ok(())
 }
 
+// The step the overlays show (or will show once open), in the form it was sent
 #[tauri::command]
-async fn close_screen_overlay(app: tauri::AppHandle) -> Result<(), String> {
-    if let Some(window) = app.get_webview_window("screen-overlay") {
-        window.close().map_err(|e| e.to_string())?;
+async fn get_overlay_state(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<OverlaySnapshot, String> {
+    let current = state.overlay.lock().unwrap().clone();
+    let mut open_screens: Vec<u32> = overlay::windows(&app).into_iter().map(|(id, _)| id).collect();
+    open_screens.sort_unstable();
+
+    Ok(OverlaySnapshot { area: current.area, payload: current.payload, hidden: current.hidden, open_screens })
+}
+
+// Close every overlay window, including an open region selection
+fn close_overlays(app: &tauri::AppHandle) {
+    for (label, window) in app.webview_windows() {
+        if label.starts_with("screen-overlay") {
+            let _ = window.destroy();
+        }
     }
+    app.state::<AppState>().click_listener.set_targets(Vec::new());
+}
+
+// Cover a screen with the overlay in selection mode, let the user drag a
+// rectangle and capture just that region. Resolves to None if they press ESC.
+#[tauri::command]
+async fn start_region_selection(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    screen: Option<ScreenSelector>,
+    encoding: Option<EncodeOptions>,
+    session_id: Option<String>,
+) -> Result<Option<RegionCapture>, String> {
+    // Replaces any step overlays; an earlier selection resolves as cancelled
+    close_overlays(&app);
+
+    let (screen, _) = resolve_capture_area(&state, screen)?;
+    let overlay = screen.bounds();
+    let window = build_overlay_window(&app, "screen-overlay-selection", "overlay.html?mode=select", overlay)?;
+    let _ = window.set_focus();
+
+    let bounds = selection::wait_for_region(&window).await;
+    let _ = window.destroy();
+
+    let Some(rect) = bounds.and_then(|bounds| selection::to_desktop(&bounds, &overlay)) else {
+        return Ok(None);
+    };
+
+    let capture = screenshot(&app, &state, None, Some(rect), encoding, None, session_id, None).await?;
+    Ok(Some(RegionCapture { rect, capture }))
+}
+
+#[tauri::command]
+async fn close_screen_overlay(app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<(), String> {
+    close_overlays(&app);
+    *state.overlay.lock().unwrap() = OverlayState::default();
     Ok(())
 }
 
@@ -281,6 +1256,7 @@ async fn close_screen_overlay(app: tauri::AppHandle) -> Result<(), String> {
 #[cfg(target_os = "macos")]
 mod window_management {
     use super::*;
+    use screenshots::Screen;
 
     pub fn get_all_windows() -> Result<Vec<FocusedWindowInfo>, String> {
         // First, check if we can access System Events (this will trigger permission prompt if needed)
@@ -311,7 +1287,9 @@ mod window_management {
                                 try
                                     set winName to name of win
                                     set winID to procID * 1000 + winIndex
-                                    set output to output & procName & "|" & winName & "|" & procID & "|" & winID & "\n"
+                                    set {winX, winY} to position of win
+                                    set {winW, winH} to size of win
+                                    set output to output & procName & "|" & winName & "|" & procID & "|" & winID & "|" & winX & "|" & winY & "|" & winW & "|" & winH & "\n"
                                     set winIndex to winIndex + 1
                                 end try
                             end repeat
@@ -340,7 +1318,7 @@ mod window_management {
         let result = String::from_utf8_lossy(&output.stdout);
         println!("[Prism] AppleScript output: {:?}", result);
 
-        // Parse the AppleScript result - format is: app1|window1|pid1|winID1\napp2|window2|pid2|winID2\n...
+        // Parse the AppleScript result - format is: app1|window1|pid1|winID1|x1|y1|w1|h1\napp2|window2|...
         let mut windows = Vec::new();
 
         for line in result.lines() {
@@ -350,11 +1328,15 @@ mod window_management {
             }
 
             let parts: Vec<&str> = line.split('|').collect();
-            if parts.len() == 4 {
+            if parts.len() >= 8 {
+                // Window titles may themselves contain '|', so numeric fields are read from the end
+                let n = parts.len();
+                let number = |i: usize| parts[i].trim().parse::<f64>().unwrap_or(0.0);
+
                 let owner_name = parts[0].trim().to_string();
-                let window_name = parts[1].trim().to_string();
-                let process_id = parts[2].trim().parse::<i32>().unwrap_or(0);
-                let window_id = parts[3].trim().parse::<i64>().unwrap_or(0);
+                let window_name = parts[1..n - 6].join("|").trim().to_string();
+                let process_id = parts[n - 6].trim().parse::<i32>().unwrap_or(0);
+                let window_id = parts[n - 5].trim().parse::<i64>().unwrap_or(0);
 
                 println!("[Prism] Found window: {} - {} (PID: {}, WinID: {})", owner_name, window_name, process_id, window_id);
 
@@ -363,6 +1345,11 @@ mod window_management {
                     window_name,
                     window_id,
                     process_id,
+                    native_id: None,
+                    x: number(n - 4),
+                    y: number(n - 3),
+                    width: number(n - 2),
+                    height: number(n - 1),
                 });
             }
         }
@@ -371,6 +1358,50 @@ mod window_management {
         Ok(windows)
     }
 
+    // Returns the current bounds of a window in desktop points
+    pub fn get_window_frame(window: &FocusedWindowInfo) -> Result<Rect, String> {
+        let window_index = ((window.window_id % 1000) as i32).max(1);
+
+        let script = format!(
+            r#"
+            tell application "System Events"
+                tell process "{}"
+                    tell window {}
+                        set {{x, y}} to position
+                        set {{w, h}} to size
+                    end tell
+                end tell
+            end tell
+            return (x as text) & "|" & (y as text) & "|" & (w as text) & "|" & (h as text)
+            "#,
+            window.owner_name,
+            window_index
+        );
+
+        let output = std::process::Command::new("osascript")
+            .arg("-e")
+            .arg(&script)
+            .output()
+            .map_err(|e| format!("Failed to execute AppleScript: {}", e))?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Failed to read window frame: {}", error));
+        }
+
+        let result = String::from_utf8_lossy(&output.stdout);
+        let parts: Vec<f64> = result
+            .trim()
+            .split('|')
+            .filter_map(|part| part.trim().parse::<f64>().ok())
+            .collect();
+
+        match parts.as_slice() {
+            [x, y, width, height] => Ok(Rect { x: *x, y: *y, width: *width, height: *height }),
+            _ => Err(format!("Unexpected window frame output: {:?}", result)),
+        }
+    }
+
     pub fn arrange_windows(
         focused_window: &FocusedWindowInfo,
         _prism_window: &WebviewWindow
@@ -379,7 +1410,7 @@ mod window_management {
 
         // Get screen dimensions
         let screens = Screen::all().map_err(|e| e.to_string())?;
-        let screen = screens.get(0).ok_or("No screen found")?;
+        let screen = screens.first().ok_or("No screen found")?;
 
         let screen_width = screen.display_info.width as f64;
         let screen_height = screen.display_info.height as f64;
@@ -480,6 +1511,14 @@ async fn arrange_windows(
 
     window_management::arrange_windows(&window_info, &prism_window)?;
 
+    // Record where the window actually ended up after arrangement
+    let mut window_info = window_info;
+    if let Ok(frame) = window_management::get_window_frame(&window_info) {
+        window_info.set_frame(frame);
+        window_info.native_id = backend::macos::window_number(window_info.process_id, &frame);
+        *state.focused_frame_read_at.lock().unwrap() = Some(std::time::Instant::now());
+    }
+
     // Save to state and disk
     *state.focused_window.lock().unwrap() = Some(window_info.clone());
     AppState::save_to_disk(&app, &window_info)?;
@@ -487,6 +1526,29 @@ async fn arrange_windows(
     Ok(())
 }
 
+// Window listing and arrangement use System Events, so elsewhere the selected
+// window is only remembered
+#[tauri::command]
+#[cfg(not(target_os = "macos"))]
+async fn get_available_windows() -> Result<Vec<FocusedWindowInfo>, String> {
+    Err("Window listing is only supported on macOS".to_string())
+}
+
+#[tauri::command]
+#[cfg(not(target_os = "macos"))]
+async fn arrange_windows(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    window_info: FocusedWindowInfo
+) -> Result<(), String> {
+    // The window was just picked from outside Prism, so it is the active one
+    let mut window_info = window_info;
+    window_info.native_id = window_info.native_id.or_else(|| state.backend.active_window());
+
+    *state.focused_window.lock().unwrap() = Some(window_info.clone());
+    AppState::save_to_disk(&app, &window_info)
+}
+
 #[tauri::command]
 async fn start_focus_selection_mode(
     app: tauri::AppHandle,
@@ -522,12 +1584,39 @@ pub fn run() {
   tauri::Builder::default()
     .plugin(tauri_plugin_global_shortcut::Builder::new().build())
     .manage(AppState::new())
+    .register_uri_scheme_protocol(capture_store::CAPTURE_SCHEME, |ctx, request| {
+      capture_protocol_response(ctx.app_handle(), &request)
+    })
     .invoke_handler(tauri::generate_handler![
+      list_screens,
       take_screenshot,
+      get_capture_bytes,
+      render_annotated_capture,
+      copy_annotated_capture,
+      load_image_file,
+      load_clipboard_image,
+      list_captures,
+      get_capture,
+      delete_captures,
+      get_retention_policy,
+      set_retention_policy,
+      compare_captures,
+      start_screen_watcher,
+      stop_screen_watcher,
+      start_cursor_stream,
+      stop_cursor_stream,
+      start_click_listener,
+      stop_click_listener,
+      get_redaction_rules,
+      set_redaction_rules,
+      get_overlay_theme,
+      set_overlay_theme,
       open_settings_window,
       open_screen_overlay,
       update_screen_overlay_data,
+      get_overlay_state,
       close_screen_overlay,
+      start_region_selection,
       get_available_windows,
       arrange_windows,
       start_focus_selection_mode,
@@ -544,6 +1633,22 @@ pub fn run() {
         )?;
       }
 
+      *app.state::<AppState>().redaction_rules.lock().unwrap() = redaction::load(app.handle());
+      *app.state::<AppState>().overlay_theme.lock().unwrap() = theme::load(app.handle());
+
+      // Keep captures on disk and sweep them according to the retention policy
+      let policy = history::load_policy(app.handle());
+      let opened = history::history_dir(app.handle())
+        .and_then(|dir| app.state::<AppState>().history.open(dir, policy));
+      if let Err(e) = opened {
+        println!("[Prism] Capture history disabled: {}", e);
+      }
+      history::spawn_sweeper(app.handle().clone());
+
+      // Overlays get the current step from the backend whenever they load
+      overlay::replay_on_ready(app.handle());
+      visibility::reappear_on_change(app.handle());
+
       // Register global shortcut for Proceed button (Cmd+Enter)
       let handle = app.handle().clone();
       app.global_shortcut().on_shortcut("CmdOrCtrl+Enter", move |_app, _shortcut, event| {
@@ -551,6 +1656,13 @@ pub fn run() {
           let _ = handle.emit("proceed-shortcut-triggered", ());
         }
       })?;
+
+      // Peek under the overlays (press again to bring them back)
+      app.global_shortcut().on_shortcut(visibility::PEEK_SHORTCUT, |app, _shortcut, event| {
+        if event.state == ShortcutState::Pressed {
+          visibility::toggle(app);
+        }
+      })?;
       #[cfg(target_os = "macos")]
       {
         use tauri::menu::{MenuBuilder, MenuItemBuilder, SubmenuBuilder};
@@ -577,6 +1689,16 @@ pub fn run() {
         });
       }
 
+      // Restore the saved focus window without arranging
+      #[cfg(not(target_os = "macos"))]
+      {
+        let state = app.state::<AppState>();
+        if let Some(saved_window) = AppState::load_from_disk(app.handle()) {
+          println!("[Prism] Found saved window: {:?}", saved_window);
+          *state.focused_window.lock().unwrap() = Some(saved_window);
+        }
+      }
+
       // Auto-arrangement on startup
       #[cfg(target_os = "macos")]
       {
@@ -617,3 +1739,265 @@ pub fn run() {
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend::FakeBackend;
+    use screenshots::image::{self, Rgba};
+
+    fn state_with_screens(sizes: &[(u32, u32)]) -> AppState {
+        let images = sizes
+            .iter()
+            .map(|&(width, height)| RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255])))
+            .collect();
+        AppState::with_backend(Box::new(FakeBackend::from_images(images)))
+    }
+
+    fn focused_window(x: f64, y: f64, width: f64, height: f64) -> FocusedWindowInfo {
+        FocusedWindowInfo {
+            owner_name: "Test".to_string(),
+            window_name: "Test window".to_string(),
+            window_id: 1,
+            process_id: 1,
+            native_id: None,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn capture(state: &AppState, selector: Option<ScreenSelector>, masks: Vec<(String, Rect)>) -> CaptureResult {
+        let grab = grab_screen(state, selector).unwrap();
+        finish_capture(state, grab, None, &[], masks, Vec::new(), &EncodeOptions::default()).unwrap()
+    }
+
+    fn stored_image(state: &AppState, result: &CaptureResult) -> RgbaImage {
+        let stored = state.captures.get(&result.info.capture_id).unwrap();
+        image::load_from_memory(&stored.bytes).unwrap().to_rgba8()
+    }
+
+    #[test]
+    fn captures_primary_screen_without_focused_window() {
+        let state = state_with_screens(&[(200, 100), (300, 150)]);
+        let result = capture(&state, None, Vec::new());
+
+        assert_eq!(result.info.screen_id, 0);
+        assert_eq!((result.info.crop.width, result.info.crop.height), (200, 100));
+        assert_eq!(stored_image(&state, &result).dimensions(), (200, 100));
+    }
+
+    #[test]
+    fn crops_to_focused_window_on_its_screen() {
+        let state = state_with_screens(&[(200, 100), (300, 150)]);
+        *state.focused_window.lock().unwrap() = Some(focused_window(250.0, 20.0, 100.0, 50.0));
+
+        let result = capture(&state, None, Vec::new());
+
+        assert_eq!(result.info.screen_id, 1);
+        assert_eq!(result.info.crop_logical, Rect { x: 250.0, y: 20.0, width: 100.0, height: 50.0 });
+        assert_eq!((result.info.crop.x, result.info.crop.y), (50, 20));
+        assert_eq!(stored_image(&state, &result).dimensions(), (100, 50));
+    }
+
+    #[test]
+    fn explicit_screen_ignores_focused_window() {
+        let state = state_with_screens(&[(200, 100), (300, 150)]);
+        *state.focused_window.lock().unwrap() = Some(focused_window(250.0, 20.0, 100.0, 50.0));
+
+        let result = capture(&state, Some(ScreenSelector::Id(0)), Vec::new());
+        assert_eq!(result.info.screen_id, 0);
+        assert_eq!((result.info.crop.width, result.info.crop.height), (200, 100));
+
+        assert!(grab_screen(&state, Some(ScreenSelector::Id(7))).is_err());
+    }
+
+    #[test]
+    fn masks_prism_windows_out_of_the_image() {
+        let state = state_with_screens(&[(200, 100)]);
+        let masks = vec![("main".to_string(), Rect { x: 150.0, y: 0.0, width: 50.0, height: 100.0 })];
+
+        let result = capture(&state, None, masks);
+        let image = stored_image(&state, &result);
+
+        assert_eq!(result.info.excluded_windows.len(), 1);
+        assert_eq!(result.info.excluded_windows[0].method, ExclusionMethod::Masked);
+        assert_eq!(image.get_pixel(175, 50)[0], 0);
+        assert_eq!(image.get_pixel(50, 50)[0], 255);
+    }
+
+    #[test]
+    fn composites_cursor_when_requested() {
+        let backend = FakeBackend::from_images(vec![RgbaImage::from_pixel(200, 100, Rgba([0, 0, 255, 255]))]);
+        backend.set_cursor(Some((50.0, 25.0)));
+        let state = AppState::with_backend(Box::new(backend));
+
+        let mut grab = grab_screen(&state, None).unwrap();
+        grab.cursor = state.backend.cursor_position();
+        let result = finish_capture(&state, grab, None, &[], Vec::new(), Vec::new(), &EncodeOptions::default()).unwrap();
+        let image = stored_image(&state, &result);
+
+        let cursor = result.info.cursor.unwrap();
+        assert_eq!((cursor.x, cursor.y), (0.25, 0.25));
+        // Outline at the tip, white fill inside the arrow, background untouched
+        assert_eq!(image.get_pixel(50, 26).0, [0, 0, 0, 255]);
+        assert_eq!(image.get_pixel(53, 35).0, [255, 255, 255, 255]);
+        assert_eq!(image.get_pixel(70, 25).0, [0, 0, 255, 255]);
+        assert!(capture(&state, None, Vec::new()).info.cursor.is_none());
+    }
+
+    #[test]
+    fn captures_window_contents_instead_of_screen_pixels() {
+        let backend = FakeBackend::from_images(vec![RgbaImage::from_pixel(200, 100, Rgba([255, 255, 255, 255]))]);
+        let frame = Rect { x: 40.0, y: 10.0, width: 80.0, height: 60.0 };
+        backend.add_window(42, frame, RgbaImage::from_pixel(80, 60, Rgba([200, 0, 0, 255])));
+        let state = AppState::with_backend(Box::new(backend));
+        *state.focused_window.lock().unwrap() = Some(FocusedWindowInfo {
+            native_id: Some(42),
+            ..focused_window(0.0, 0.0, 100.0, 100.0)
+        });
+
+        let grab = grab_focused_window(&state, &None, None).unwrap().unwrap();
+        let result = finish_capture(&state, grab, None, &[], Vec::new(), Vec::new(), &EncodeOptions::default()).unwrap();
+        let image = stored_image(&state, &result);
+
+        assert_eq!(result.info.source, CaptureSource::Window);
+        assert_eq!(result.info.crop_logical, frame);
+        assert_eq!(result.info.screen_id, 0);
+        assert_eq!(image.dimensions(), (80, 60));
+        assert_eq!(image.get_pixel(40, 30).0, [200, 0, 0, 255]);
+        assert!(grab_window(&state, 7).is_err());
+    }
+
+    #[test]
+    fn captures_the_screen_without_a_native_window_id() {
+        let backend = FakeBackend::from_images(vec![RgbaImage::from_pixel(200, 100, Rgba([255, 255, 255, 255]))]);
+        backend.add_window(1, Rect { x: 0.0, y: 0.0, width: 80.0, height: 60.0 }, RgbaImage::new(80, 60));
+        let state = AppState::with_backend(Box::new(backend));
+
+        // window_id alone is not a window server id, even when it happens to match one
+        *state.focused_window.lock().unwrap() = Some(focused_window(0.0, 0.0, 100.0, 100.0));
+        assert!(grab_focused_window(&state, &None, None).is_none());
+
+        // A native id the backend can't capture is reported, then the screen is used
+        *state.focused_window.lock().unwrap() = Some(FocusedWindowInfo {
+            native_id: Some(7),
+            ..focused_window(0.0, 0.0, 100.0, 100.0)
+        });
+        assert!(grab_focused_window(&state, &None, None).unwrap().is_err());
+        assert!(grab_focused_window(&state, &Some(ScreenSelector::Named(NamedScreen::Primary)), None).is_none());
+
+        let grab = grab_screen(&state, None).unwrap();
+        assert_eq!(grab.source, CaptureSource::Screen);
+        assert_eq!(grab.area, Rect { x: 0.0, y: 0.0, width: 100.0, height: 100.0 });
+    }
+
+    #[test]
+    fn imports_images_without_desktop_rules() {
+        let state = state_with_screens(&[(200, 100)]);
+        *state.focused_window.lock().unwrap() = Some(focused_window(0.0, 0.0, 100.0, 100.0));
+        *state.redaction_rules.lock().unwrap() = vec![RedactionRule {
+            id: "everything".to_string(),
+            label: None,
+            enabled: true,
+            style: redaction::RedactionStyle::Blackout,
+            target: redaction::RedactionTarget::Rect { rect: Rect { x: 0.0, y: 0.0, width: 200.0, height: 100.0 } },
+        }];
+
+        let image = RgbaImage::from_pixel(320, 240, Rgba([10, 200, 10, 255]));
+        let result = import_image(&state, image, CaptureSource::Clipboard, None, &EncodeOptions::default()).unwrap();
+        let stored = stored_image(&state, &result);
+
+        assert_eq!(result.info.source, CaptureSource::Clipboard);
+        assert_eq!(result.info.crop_logical, Rect { x: 0.0, y: 0.0, width: 320.0, height: 240.0 });
+        assert!(result.info.redactions.is_empty());
+        assert!(result.info.focused_window.is_none());
+        assert_eq!(stored.get_pixel(160, 120).0, [10, 200, 10, 255]);
+        assert!(import_image(&state, RgbaImage::new(0, 0), CaptureSource::File, None, &EncodeOptions::default()).is_err());
+    }
+
+    #[test]
+    fn persists_captures_and_enforces_retention() {
+        let dir = std::env::temp_dir().join(format!("prism-history-{}", std::process::id()));
+        let state = state_with_screens(&[(200, 100)]);
+        state.history.open(dir.clone(), RetentionPolicy::default()).unwrap();
+
+        let grab = grab_screen(&state, None).unwrap();
+        let first = finish_capture(&state, grab, Some("walkthrough-1".to_string()), &[], Vec::new(), Vec::new(), &EncodeOptions::default()).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let second = capture(&state, None, Vec::new());
+
+        let record = state.history.get(&first.info.capture_id).unwrap();
+        assert_eq!(record.info.session_id.as_deref(), Some("walkthrough-1"));
+        assert_eq!(state.history.list(Some("walkthrough-1")).len(), 1);
+        assert_eq!(state.history.list(None).len(), 2);
+        assert!(state.history.read_image(&second.info.capture_id).is_some());
+        assert!(state.history.get("../redactions").is_none());
+
+        // Captures dropped from memory are still compared from history
+        state.captures.remove(std::slice::from_ref(&first.info.capture_id));
+        let (before, area) = capture_fingerprint(&state, &first.info.capture_id).unwrap();
+        let (after, _) = capture_fingerprint(&state, &second.info.capture_id).unwrap();
+        assert!(!change::compare(&before, &after, change::DEFAULT_THRESHOLD, &area).changed);
+        assert!(capture_fingerprint(&state, "missing").is_err());
+
+        // An age limit too large to express in milliseconds keeps everything
+        state.history.set_policy(RetentionPolicy { max_age_hours: Some(u64::MAX), max_total_bytes: None });
+        assert!(state.history.sweep(capture::now_millis()).is_empty());
+
+        // Both captures are younger than an hour; a one-capture byte budget keeps the newest
+        state.history.set_policy(RetentionPolicy {
+            max_age_hours: Some(1),
+            max_total_bytes: Some(record.bytes),
+        });
+        let deleted = state.history.sweep(capture::now_millis());
+        assert_eq!(deleted, vec![first.info.capture_id.clone()]);
+
+        // Everything is older than an hour from two hours in the future
+        let later = capture::now_millis() + 2 * 60 * 60 * 1000;
+        assert_eq!(state.history.sweep(later), vec![second.info.capture_id.clone()]);
+        assert!(state.history.list(None).is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn applies_and_reports_redaction_rules() {
+        let state = state_with_screens(&[(200, 100)]);
+        *state.focused_window.lock().unwrap() = Some(focused_window(0.0, 0.0, 100.0, 100.0));
+        *state.redaction_rules.lock().unwrap() = vec![
+            RedactionRule {
+                id: "sidebar".to_string(),
+                label: Some("Password manager".to_string()),
+                enabled: true,
+                style: redaction::RedactionStyle::Blackout,
+                target: redaction::RedactionTarget::Rect { rect: Rect { x: 80.0, y: 0.0, width: 100.0, height: 20.0 } },
+            },
+            RedactionRule {
+                id: "drawn".to_string(),
+                label: None,
+                enabled: true,
+                style: redaction::RedactionStyle::Blackout,
+                target: redaction::RedactionTarget::Region { bounds: Rect { x: 0.0, y: 0.5, width: 0.5, height: 0.5 } },
+            },
+            RedactionRule {
+                id: "outside".to_string(),
+                label: None,
+                enabled: true,
+                style: redaction::RedactionStyle::Blackout,
+                target: redaction::RedactionTarget::Rect { rect: Rect { x: 150.0, y: 0.0, width: 50.0, height: 100.0 } },
+            },
+        ];
+
+        let result = capture(&state, None, Vec::new());
+        let image = stored_image(&state, &result);
+
+        let applied: Vec<&str> = result.info.redactions.iter().map(|r| r.rule_id.as_str()).collect();
+        assert_eq!(applied, vec!["sidebar", "drawn"]);
+        // Clipped to the capture area
+        assert_eq!(result.info.redactions[0].area, Rect { x: 80.0, y: 0.0, width: 20.0, height: 20.0 });
+        assert_eq!(image.get_pixel(90, 10)[0], 0);
+        assert_eq!(image.get_pixel(25, 75)[0], 0);
+        assert_eq!(image.get_pixel(25, 25)[0], 255);
+    }
+}
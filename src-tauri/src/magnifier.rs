@@ -0,0 +1,255 @@
+use base64::Engine;
+use screenshots::image::imageops::{self, FilterType};
+use serde::{Deserialize, Serialize};
+
+use crate::capture::{CaptureInfo, Rect, ScreenInfo};
+use crate::encode::{self, EncodeOptions};
+use crate::hotspots::{self, Hotspot};
+use crate::{load_capture, AppState, OverlayPayload};
+
+const MAX_ZOOM: f64 = 8.0;
+const MAX_RADIUS: f64 = 200.0;
+// The callout never takes more than this share of its screen's shorter side
+const MAX_CALLOUT_FRACTION: f64 = 0.4;
+// Longest side of the encoded inset, in pixels
+const MAX_INSET_EDGE: u32 = 1024;
+
+// Zoomed callout of the region around the step's first target, for targets
+// too small to make out. Sent with a step to turn it on for that step.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Magnifier {
+    // Lowered by the backend when the callout would take too much of the screen
+    pub zoom: f64,
+    // Logical pixels around the target's center that go into the inset
+    pub radius: f64,
+    // Targets larger than this (logical pixels, either side) are left alone
+    pub max_target: f64,
+    // Logical pixels between the magnified region and the callout
+    pub gap: f64,
+    // Filled in by the backend; anything sent in is ignored. `image` is a PNG
+    // data URL. `source` (the magnified region) and `callout` are in logical
+    // desktop pixels until routed, then in logical pixels of the overlay.
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub source: Option<Rect>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub callout: Option<Rect>,
+}
+
+impl Default for Magnifier {
+    fn default() -> Self {
+        Self {
+            zoom: 3.0,
+            radius: 40.0,
+            max_target: 48.0,
+            gap: 16.0,
+            image: None,
+            source: None,
+            callout: None,
+        }
+    }
+}
+
+fn validate(magnifier: &Magnifier) -> Result<(), String> {
+    if !(magnifier.zoom > 1.0 && magnifier.zoom <= MAX_ZOOM) {
+        return Err(format!("Magnifier zoom must be above 1 and at most {}", MAX_ZOOM));
+    }
+    if !(magnifier.radius > 0.0 && magnifier.radius <= MAX_RADIUS) {
+        return Err(format!("Magnifier radius must be above 0 and at most {}", MAX_RADIUS));
+    }
+    let sizes = [magnifier.max_target, magnifier.gap];
+    if !sizes.iter().all(|size| size.is_finite() && *size >= 0.0) {
+        return Err("Magnifier max target and gap must be zero or more".to_string());
+    }
+    Ok(())
+}
+
+// Magnifier settings passed to an overlay command
+pub fn accept(magnifier: Option<Magnifier>) -> Result<Option<Magnifier>, String> {
+    if let Some(magnifier) = &magnifier {
+        validate(magnifier)?;
+    }
+    Ok(magnifier)
+}
+
+fn target_rect(hotspot: &Hotspot) -> Rect {
+    match hotspot {
+        Hotspot::Point(x, y) => Rect { x: *x, y: *y, width: 0.0, height: 0.0 },
+        Hotspot::Rect(rect) => *rect,
+        Hotspot::Polygon(points) | Hotspot::Path(points) => Rect::enclosing(points),
+    }
+}
+
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+// Where a `width` x `height` callout goes so it stays on `screen` without
+// covering `source`: beside it, then above or below, preferring the side with
+// more room. If nothing fits, the callout is pushed into the screen's far corner.
+pub fn place(source: &Rect, width: f64, height: f64, screen: &Rect, gap: f64) -> Rect {
+    let (cx, cy) = source.center();
+    let (screen_cx, screen_cy) = screen.center();
+    let right = source.x + source.width + gap;
+    let left = source.x - gap - width;
+    let below = source.y + source.height + gap;
+    let above = source.y - gap - height;
+    let (beside_y, stacked_x) = (cy - height / 2.0, cx - width / 2.0);
+
+    // Toward the middle of the screen first
+    let (near_x, far_x) = if cx < screen_cx { (right, left) } else { (left, right) };
+    let (near_y, far_y) = if cy < screen_cy { (below, above) } else { (above, below) };
+    let candidates = [
+        (near_x, beside_y),
+        (far_x, beside_y),
+        (stacked_x, near_y),
+        (stacked_x, far_y),
+        (near_x, near_y),
+    ];
+
+    let on_screen = |x: f64, y: f64| Rect {
+        x: x.clamp(screen.x, (screen.x + screen.width - width).max(screen.x)),
+        y: y.clamp(screen.y, (screen.y + screen.height - height).max(screen.y)),
+        width,
+        height,
+    };
+    candidates
+        .iter()
+        .map(|(x, y)| on_screen(*x, *y))
+        .find(|rect| !overlaps(rect, source))
+        .unwrap_or_else(|| {
+            let x = if cx < screen_cx { f64::MAX } else { f64::MIN };
+            let y = if cy < screen_cy { f64::MAX } else { f64::MIN };
+            on_screen(x, y)
+        })
+}
+
+// Zoom for a `size` x `size` region so its callout fits on `screen`; at most
+// the requested zoom, and 1 or less when the region is already large there
+fn callout_zoom(zoom: f64, size: f64, screen: &Rect) -> f64 {
+    let limit = MAX_CALLOUT_FRACTION * screen.width.min(screen.height) / size.max(1.0);
+    zoom.min(limit)
+}
+
+// Pixel size of a `width` x `height` crop scaled up `zoom` times, capped at MAX_INSET_EDGE
+fn inset_size(width: u32, height: u32, zoom: f64) -> (u32, u32) {
+    let zoom = zoom.min(MAX_INSET_EDGE as f64 / width.max(height).max(1) as f64);
+    (
+        ((width as f64 * zoom).round() as u32).clamp(1, MAX_INSET_EDGE),
+        ((height as f64 * zoom).round() as u32).clamp(1, MAX_INSET_EDGE),
+    )
+}
+
+// PNG data URL of `source` (logical desktop pixels) cut out of a capture and
+// scaled up `zoom` times; None when the capture doesn't show that region
+fn crop(info: &CaptureInfo, bytes: &[u8], source: &Rect, zoom: f64) -> Option<(String, Rect)> {
+    let visible = source.intersect(&info.crop_logical)?;
+    let image = screenshots::image::load_from_memory(bytes).ok()?.to_rgba8();
+
+    // Encoding may have downscaled the capture
+    let scale = image.width() as f64 / info.crop_logical.width.max(1.0);
+    let x = ((visible.x - info.crop_logical.x) * scale).floor().max(0.0) as u32;
+    let y = ((visible.y - info.crop_logical.y) * scale).floor().max(0.0) as u32;
+    let width = ((visible.width * scale).ceil() as u32).min(image.width().saturating_sub(x));
+    let height = ((visible.height * scale).ceil() as u32).min(image.height().saturating_sub(y));
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let region = imageops::crop_imm(&image, x, y, width, height).to_image();
+    let (zoomed_width, zoomed_height) = inset_size(width, height, zoom);
+    let zoomed = imageops::resize(&region, zoomed_width, zoomed_height, FilterType::CatmullRom);
+    let (png, _) = encode::encode(&zoomed, &EncodeOptions::default()).ok()?;
+    let url = format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(png));
+    Some((url, visible))
+}
+
+// Fill in the inset for a step from `capture_id`, or the latest desktop
+// capture. Without a small target or a capture showing it, the step goes out
+// without an inset.
+pub fn prepare(state: &AppState, payload: &mut OverlayPayload, area: &Rect, screens: &[ScreenInfo], capture_id: Option<&str>) {
+    let Some(mut magnifier) = payload.magnifier.take() else {
+        return;
+    };
+    magnifier.image = None;
+    magnifier.source = None;
+    magnifier.callout = None;
+
+    let Some(target) = hotspots::from_payload(payload, area, screens).first().map(target_rect) else {
+        payload.magnifier = Some(magnifier);
+        return;
+    };
+    let capture = capture_id
+        .and_then(|id| load_capture(state, id))
+        .or_else(|| state.captures.latest_desktop().map(|c| (c.info, c.mime_type.to_string(), c.bytes.as_ref().clone())))
+        .filter(|(info, _, _)| info.source.is_desktop());
+
+    let (cx, cy) = target.center();
+    let screen = screens.iter().find(|s| s.bounds().contains(cx, cy));
+    let is_small = target.width.max(target.height) <= magnifier.max_target;
+    if let (Some((info, _, bytes)), Some(screen), true) = (capture, screen, is_small) {
+        let half = magnifier.radius.max(target.width / 2.0).max(target.height / 2.0);
+        let region = Rect { x: cx - half, y: cy - half, width: 2.0 * half, height: 2.0 * half };
+        let zoom = callout_zoom(magnifier.zoom, region.width, &screen.bounds());
+        if let Some((image, source)) = crop(&info, &bytes, &region, zoom).filter(|_| zoom > 1.0) {
+            let callout = place(&source, source.width * zoom, source.height * zoom, &screen.bounds(), magnifier.gap);
+            magnifier.zoom = zoom;
+            magnifier.image = Some(image);
+            magnifier.source = Some(source);
+            magnifier.callout = Some(callout);
+        }
+    }
+    payload.magnifier = Some(magnifier);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn places_the_callout_on_screen_without_covering_the_target() {
+        let screen = Rect { x: 0.0, y: 0.0, width: 1000.0, height: 800.0 };
+        let rect = |x, y| Rect { x, y, width: 80.0, height: 80.0 };
+
+        // Beside the target, toward the middle of the screen
+        assert_eq!(place(&rect(100.0, 360.0), 240.0, 240.0, &screen, 16.0).x, 196.0);
+        assert_eq!(place(&rect(820.0, 360.0), 240.0, 240.0, &screen, 16.0).x, 564.0);
+
+        // Too wide to fit beside, so it goes below, pulled onto the screen
+        let callout = place(&rect(460.0, 40.0), 480.0, 240.0, &screen, 16.0);
+        assert_eq!((callout.x, callout.y), (260.0, 136.0));
+
+        for (x, y) in [(0.0, 0.0), (920.0, 720.0), (460.0, 360.0)] {
+            let source = rect(x, y);
+            let callout = place(&source, 240.0, 240.0, &screen, 16.0);
+            assert!(!overlaps(&callout, &source), "{:?}", callout);
+            assert!(callout.x >= 0.0 && callout.y >= 0.0);
+            assert!(callout.x + callout.width <= 1000.0 && callout.y + callout.height <= 800.0);
+        }
+    }
+
+    #[test]
+    fn keeps_large_callouts_within_the_screen() {
+        let screen = Rect { x: 0.0, y: 0.0, width: 1000.0, height: 800.0 };
+
+        // A small region keeps its zoom; the largest radius at the largest zoom
+        // is cut down to 40% of the screen's height
+        assert_eq!(callout_zoom(3.0, 80.0, &screen), 3.0);
+        assert_eq!(callout_zoom(MAX_ZOOM, 2.0 * MAX_RADIUS, &screen) * 2.0 * MAX_RADIUS, 320.0);
+        assert!(callout_zoom(MAX_ZOOM, 1000.0, &screen) <= 1.0);
+
+        // Encoded insets are capped too, keeping their aspect ratio
+        assert_eq!(inset_size(80, 40, 3.0), (240, 120));
+        assert_eq!(inset_size(800, 400, MAX_ZOOM), (MAX_INSET_EDGE, MAX_INSET_EDGE / 2));
+    }
+
+    #[test]
+    fn rejects_out_of_range_settings() {
+        assert!(validate(&Magnifier::default()).is_ok());
+        assert!(validate(&Magnifier { zoom: 1.0, ..Default::default() }).is_err());
+        assert!(validate(&Magnifier { radius: 0.0, ..Default::default() }).is_err());
+        assert!(validate(&Magnifier { gap: -1.0, ..Default::default() }).is_err());
+    }
+}
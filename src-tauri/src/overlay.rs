@@ -0,0 +1,517 @@
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Listener, Manager, WebviewWindow};
+
+use crate::capture::{Rect, ScreenInfo};
+use crate::magnifier::Magnifier;
+use crate::shapes::{Shape, ShapeKind};
+use crate::{layout, spotlight, visibility, AppState, BoundingBox, OverlayPayload, Point};
+
+// Step overlays are labelled screen-overlay-<screen id>, one per display
+pub const LABEL_PREFIX: &str = "screen-overlay-";
+
+// What the overlays show. Owned by the backend rather than the webviews, so an
+// overlay that opens late, reloads or is reopened gets the current step.
+#[derive(Clone, Default)]
+pub struct OverlayState {
+    // Desktop area that normalized overlay items refer to
+    pub area: Option<Rect>,
+    // Last step sent, before it is split per screen
+    pub payload: Option<OverlayPayload>,
+    // Peeked away or auto-hidden; the windows stay open and keep the step
+    pub hidden: bool,
+    // Bumped on every show/hide so a pending auto-hide for an older step is dropped
+    pub generation: u64,
+}
+
+impl OverlayState {
+    // A new step always starts visible
+    pub fn set_step(&mut self, area: Rect, payload: OverlayPayload) {
+        self.area = Some(area);
+        self.payload = Some(payload);
+        self.hidden = false;
+    }
+
+    // The part of the current step one screen's overlay shows
+    pub fn for_screen(&self, screens: &[ScreenInfo], screen_id: u32) -> Option<OverlayPayload> {
+        let (payload, area) = (self.payload.as_ref()?, self.area.as_ref()?);
+        route(payload, area, screens)
+            .into_iter()
+            .find(|(screen, _)| screen.id == screen_id)
+            .map(|(_, payload)| payload)
+    }
+}
+
+// Returned by get_overlay_state
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlaySnapshot {
+    pub area: Option<Rect>,
+    pub payload: Option<OverlayPayload>,
+    pub hidden: bool,
+    // Screens that have an overlay window right now
+    pub open_screens: Vec<u32>,
+}
+
+pub fn label(screen_id: u32) -> String {
+    format!("{}{}", LABEL_PREFIX, screen_id)
+}
+
+// Open step overlays by screen id (the region selection overlay is not one of them)
+pub fn windows(app: &tauri::AppHandle) -> Vec<(u32, WebviewWindow)> {
+    app.webview_windows()
+        .into_iter()
+        .filter_map(|(label, window)| {
+            let screen_id = label.strip_prefix(LABEL_PREFIX)?.parse().ok()?;
+            Some((screen_id, window))
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct OverlayReady {
+    label: String,
+}
+
+// Send the current step to every overlay page that finishes loading. Every
+// overlay emits the same app-wide event, so the payload says which one is ready.
+pub fn replay_on_ready(app: &tauri::AppHandle) {
+    let handle = app.clone();
+    app.listen("overlay-ready", move |event| {
+        let screen_id = serde_json::from_str::<OverlayReady>(event.payload())
+            .ok()
+            .and_then(|ready| ready.label.strip_prefix(LABEL_PREFIX)?.parse().ok());
+        if let Some(screen_id) = screen_id {
+            if let Err(e) = replay(&handle, screen_id) {
+                println!("[Prism] Failed to restore overlay {}: {}", screen_id, e);
+            }
+        }
+    });
+}
+
+fn replay(app: &tauri::AppHandle, screen_id: u32) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let current = state.overlay.lock().unwrap().clone();
+    if current.payload.is_none() {
+        return Ok(());
+    }
+
+    if let Some(payload) = current.for_screen(&state.backend.screens()?, screen_id) {
+        send(app, screen_id, &payload)?;
+    }
+    if current.hidden {
+        visibility::send(app, screen_id, false)?;
+    }
+    Ok(())
+}
+
+// Send a payload to one overlay only; overlays listen on their own window
+pub fn send(app: &tauri::AppHandle, screen_id: u32, payload: &OverlayPayload) -> Result<(), String> {
+    app.emit_to(label(screen_id).as_str(), "overlay-data", payload)
+        .map_err(|e| format!("Failed to emit overlay-data: {:?}", e))
+}
+
+fn map(bounds: &Rect, x: f64, y: f64) -> (f64, f64) {
+    (bounds.x + x * bounds.width, bounds.y + y * bounds.height)
+}
+
+fn normalize(bounds: &Rect, x: f64, y: f64) -> (f64, f64) {
+    ((x - bounds.x) / bounds.width.max(1.0), (y - bounds.y) / bounds.height.max(1.0))
+}
+
+// Rectangle an item's normalized coordinates refer to: its own screen when it
+// names one, otherwise the capture area
+fn reference(screen_id: Option<u32>, area: &Rect, screens: &[ScreenInfo]) -> Rect {
+    screen_id
+        .and_then(|id| screens.iter().find(|s| s.id == id))
+        .map(|screen| screen.bounds())
+        .unwrap_or(*area)
+}
+
+pub fn point_to_desktop(point: &Point, area: &Rect, screens: &[ScreenInfo]) -> (f64, f64) {
+    if point.desktop {
+        (point.x, point.y)
+    } else {
+        map(&reference(point.screen_id, area, screens), point.x, point.y)
+    }
+}
+
+pub fn box_to_desktop(b: &BoundingBox, area: &Rect, screens: &[ScreenInfo]) -> Rect {
+    let ((x_min, y_min), (x_max, y_max)) = if b.desktop {
+        ((b.x_min, b.y_min), (b.x_max, b.y_max))
+    } else {
+        let bounds = reference(b.screen_id, area, screens);
+        (map(&bounds, b.x_min, b.y_min), map(&bounds, b.x_max, b.y_max))
+    };
+    Rect { x: x_min, y: y_min, width: x_max - x_min, height: y_max - y_min }
+}
+
+// An item re-normalized to `bounds`, whatever coordinates it was sent in
+fn point_to(point: &Point, area: &Rect, screens: &[ScreenInfo], bounds: &Rect) -> Point {
+    let (x, y) = point_to_desktop(point, area, screens);
+    let (x, y) = normalize(bounds, x, y);
+    Point { x, y, ..Default::default() }
+}
+
+fn box_to(b: &BoundingBox, area: &Rect, screens: &[ScreenInfo], bounds: &Rect) -> BoundingBox {
+    let rect = box_to_desktop(b, area, screens);
+    let (x_min, y_min) = normalize(bounds, rect.x, rect.y);
+    let (x_max, y_max) = normalize(bounds, rect.x + rect.width, rect.y + rect.height);
+    BoundingBox { x_min, y_min, x_max, y_max, ..Default::default() }
+}
+
+fn shape_to(shape: &Shape, area: &Rect, screens: &[ScreenInfo], bounds: &Rect) -> Shape {
+    let mut shape = shape.clone();
+    for point in shape.points_mut() {
+        *point = point_to(point, area, screens, bounds);
+    }
+    if let Some(b) = shape.bounds_mut() {
+        *b = box_to(b, area, screens, bounds);
+    }
+    shape
+}
+
+fn shape_center(shape: &Shape, area: &Rect, screens: &[ScreenInfo]) -> (f64, f64) {
+    if let ShapeKind::Box { bounds } = &shape.kind {
+        return box_to_desktop(bounds, area, screens).center();
+    }
+    let desktop: Vec<(f64, f64)> = shape.points().iter().map(|p| point_to_desktop(p, area, screens)).collect();
+    let count = desktop.len().max(1) as f64;
+    (
+        desktop.iter().map(|p| p.0).sum::<f64>() / count,
+        desktop.iter().map(|p| p.1).sum::<f64>() / count,
+    )
+}
+
+// Whether any item is in desktop pixels or names a screen rather than being
+// normalized to the capture area
+pub fn has_absolute_items(payload: &OverlayPayload) -> bool {
+    let absolute = |p: &Point| p.desktop || p.screen_id.is_some();
+    payload.points.iter().any(absolute)
+        || payload.boxes.iter().any(|b| b.desktop || b.screen_id.is_some())
+        || payload.shapes.iter().any(|shape| {
+            shape.points().into_iter().any(absolute)
+                || matches!(&shape.kind, ShapeKind::Box { bounds } if bounds.desktop || bounds.screen_id.is_some())
+        })
+}
+
+// The payload with every item normalized to `surface`, e.g. a capture's area
+// for drawing into the capture image
+pub fn normalize_to(payload: &OverlayPayload, area: &Rect, screens: &[ScreenInfo], surface: &Rect) -> OverlayPayload {
+    OverlayPayload {
+        points: payload.points.iter().map(|p| point_to(p, area, screens, surface)).collect(),
+        boxes: payload.boxes.iter().map(|b| box_to(b, area, screens, surface)).collect(),
+        shapes: payload.shapes.iter().map(|shape| shape_to(shape, area, screens, surface)).collect(),
+        ..payload.clone()
+    }
+}
+
+// Split a payload into one per screen. Items go to the screen they are on and
+// are re-normalized to it, since each overlay covers a whole display. The step
+// panel only appears on the screen with the capture area.
+pub fn route(payload: &OverlayPayload, area: &Rect, screens: &[ScreenInfo]) -> Vec<(ScreenInfo, OverlayPayload)> {
+    let (cx, cy) = area.center();
+    let home = screens
+        .iter()
+        .position(|s| s.bounds().contains(cx, cy))
+        .or_else(|| screens.iter().position(|s| s.is_primary))
+        .unwrap_or(0);
+    let screen_of = |x: f64, y: f64| {
+        screens
+            .iter()
+            .position(|s| s.bounds().contains(x, y))
+            .unwrap_or(home)
+    };
+
+    let mut routed: Vec<(ScreenInfo, OverlayPayload)> = screens
+        .iter()
+        .enumerate()
+        .map(|(index, screen)| {
+            let is_home = index == home;
+            (
+                screen.clone(),
+                OverlayPayload {
+                    version: payload.version,
+                    points: Vec::new(),
+                    boxes: Vec::new(),
+                    shapes: Vec::new(),
+                    walkthrough_steps: payload.walkthrough_steps.filter(|_| is_home),
+                    current_step: payload.current_step.filter(|_| is_home),
+                    instruction: payload.instruction.clone().filter(|_| is_home),
+                    caption: payload.caption.clone(),
+                    is_complete: payload.is_complete.filter(|_| is_home),
+                    style: payload.style.clone(),
+                    spotlight: payload.spotlight.clone(),
+                    visibility: None,
+                    magnifier: None,
+                    labels: Vec::new(),
+                    screen_bounds: Some(screen.bounds()),
+                    area: Some(*area),
+                    is_home: Some(is_home),
+                },
+            )
+        })
+        .collect();
+    if routed.is_empty() {
+        return routed;
+    }
+
+    for point in &payload.points {
+        let (x, y) = point_to_desktop(point, area, screens);
+        let (screen, target) = &mut routed[screen_of(x, y)];
+        target.points.push(point_to(point, area, screens, &screen.bounds()));
+    }
+
+    for b in &payload.boxes {
+        let (cx, cy) = box_to_desktop(b, area, screens).center();
+        let (screen, target) = &mut routed[screen_of(cx, cy)];
+        target.boxes.push(box_to(b, area, screens, &screen.bounds()));
+    }
+
+    // A shape stays whole on one overlay: the screen with its center, even if
+    // part of it reaches onto a neighbouring display
+    for shape in &payload.shapes {
+        let (cx, cy) = shape_center(shape, area, screens);
+        let (screen, target) = &mut routed[screen_of(cx, cy)];
+        target.shapes.push(shape_to(shape, area, screens, &screen.bounds()));
+    }
+
+    // Every display is dimmed; only those with targets get holes
+    for (screen, target) in &mut routed {
+        if let Some(mut spotlight) = target.spotlight.take() {
+            let bounds = screen.bounds();
+            spotlight.cutouts = spotlight::cutouts(target, &spotlight, bounds.width, bounds.height);
+            target.spotlight = Some(spotlight);
+        }
+    }
+
+    // The inset goes to the display with the magnified region, next to it
+    if let Some(magnifier) = &payload.magnifier {
+        let (cx, cy) = magnifier.source.map_or(area.center(), |source| source.center());
+        let (screen, target) = &mut routed[screen_of(cx, cy)];
+        let bounds = screen.bounds();
+        let relative = |rect: Rect| Rect { x: rect.x - bounds.x, y: rect.y - bounds.y, ..rect };
+        target.magnifier = Some(Magnifier {
+            source: magnifier.source.map(relative),
+            callout: magnifier.callout.map(relative),
+            ..magnifier.clone()
+        });
+    }
+
+    // Labels last, so they keep clear of everything else on their display
+    for (screen, target) in &mut routed {
+        let bounds = screen.bounds();
+        target.labels = layout::labels(target, bounds.width, bounds.height);
+    }
+
+    routed
+}
+
+// Logical pixels around a point marker that its pulse, ping ring and shadow reach
+const MARKER_REACH: f64 = 48.0;
+// Beyond a box's stroke: its pulsing inner border and the 5% scale-up
+const OUTLINE_REACH: f64 = 8.0;
+
+// The four strips along a rectangle's edge, from `inside` within it to `outside` beyond it
+fn outline(rect: &Rect, inside: f64, outside: f64) -> [Rect; 4] {
+    let (x0, y0) = (rect.x - outside, rect.y - outside);
+    let (width, height) = (rect.width + 2.0 * outside, rect.height + 2.0 * outside);
+    let band = inside + outside;
+    [
+        Rect { x: x0, y: y0, width, height: band },
+        Rect { x: x0, y: y0 + height - band, width, height: band },
+        Rect { x: x0, y: y0, width: band, height },
+        Rect { x: x0 + width - band, y: y0, width: band, height },
+    ]
+}
+
+// Desktop rectangles the overlay draws over the app: point markers, box
+// outlines, spotlight edges, labels and the magnifier callout. Inside boxes
+// and cutouts is left out, so the target itself can still be watched where
+// the overlay isn't kept out of captures.
+pub fn drawn_rects(payload: &OverlayPayload, area: &Rect, screens: &[ScreenInfo]) -> Vec<Rect> {
+    let stroke = payload.style.stroke_width;
+    let mut rects = Vec::new();
+
+    for (screen, target) in route(payload, area, screens) {
+        let bounds = screen.bounds();
+        let desktop = |rect: Rect| Rect { x: bounds.x + rect.x, y: bounds.y + rect.y, ..rect };
+
+        for point in &target.points {
+            let (x, y) = map(&bounds, point.x, point.y);
+            let size = 2.0 * MARKER_REACH;
+            rects.push(Rect { x: x - MARKER_REACH, y: y - MARKER_REACH, width: size, height: size });
+        }
+        for b in &target.boxes {
+            let rect = box_to_desktop(b, &bounds, &[]);
+            let scaled = 0.025 * rect.width.max(rect.height);
+            rects.extend(outline(&rect, stroke + OUTLINE_REACH, stroke + OUTLINE_REACH + scaled));
+        }
+        if let Some(spotlight) = &target.spotlight {
+            for cutout in &spotlight.cutouts {
+                let rect = desktop(Rect { x: cutout.x, y: cutout.y, width: cutout.width, height: cutout.height });
+                rects.extend(outline(&rect, cutout.radius.max(2.0), 2.0));
+            }
+        }
+        for label in &target.labels {
+            rects.push(desktop(Rect { x: label.x, y: label.y, width: label.width, height: label.height }));
+        }
+        if let Some(callout) = target.magnifier.as_ref().and_then(|m| m.callout) {
+            rects.push(desktop(Rect { x: callout.x - 4.0, y: callout.y - 4.0, width: callout.width + 8.0, height: callout.height + 8.0 }));
+        }
+    }
+
+    rects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(id: u32, x: i32, width: u32, height: u32) -> ScreenInfo {
+        ScreenInfo { id, x, y: 0, width, height, scale_factor: 1.0, is_primary: id == 0 }
+    }
+
+    fn payload(points: Vec<Point>, boxes: Vec<BoundingBox>) -> OverlayPayload {
+        OverlayPayload {
+            points,
+            boxes,
+            walkthrough_steps: Some(3),
+            current_step: Some(1),
+            instruction: Some("Click Save".to_string()),
+            is_complete: Some(false),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn routes_items_to_the_screen_they_are_on() {
+        let screens = vec![screen(0, 0, 1000, 500), screen(1, 1000, 2000, 1000)];
+        // Capture area straddles nothing: the right half of screen 0
+        let area = Rect { x: 500.0, y: 0.0, width: 500.0, height: 500.0 };
+        let points = vec![
+            Point { x: 0.5, y: 0.5, ..Default::default() },
+            Point { x: 0.25, y: 0.5, screen_id: Some(1), ..Default::default() },
+            Point { x: 1500.0, y: 100.0, desktop: true, ..Default::default() },
+        ];
+        let boxes = vec![BoundingBox { x_min: 0.0, y_min: 0.0, x_max: 0.2, y_max: 0.2, ..Default::default() }];
+
+        let mut input = payload(points, boxes);
+        input.caption = Some("Save".to_string());
+
+        let routed = route(&input, &area, &screens);
+
+        let (first, home) = &routed[0];
+        assert_eq!(first.id, 0);
+        assert_eq!(home.is_home, Some(true));
+        assert_eq!(home.instruction.as_deref(), Some("Click Save"));
+        assert_eq!((home.points[0].x, home.points[0].y), (0.75, 0.5));
+        assert_eq!((home.boxes[0].x_min, home.boxes[0].x_max), (0.5, 0.6));
+
+        let (second, other) = &routed[1];
+        assert_eq!(second.id, 1);
+        assert!(other.instruction.is_none());
+        assert_eq!(other.points.len(), 2);
+        assert_eq!((other.points[0].x, other.points[0].y), (0.25, 0.5));
+        assert_eq!((other.points[1].x, other.points[1].y), (0.25, 0.1));
+
+        // Captions are laid out per display, one per item there
+        assert_eq!(home.labels.len(), 2);
+        assert_eq!(other.labels.iter().map(|label| label.index).collect::<Vec<_>>(), vec![0, 1]);
+    }
+
+    #[test]
+    fn routes_shapes_whole_to_the_screen_with_their_center() {
+        let screens = vec![screen(0, 0, 1000, 500), screen(1, 1000, 2000, 1000)];
+        let area = Rect { x: 500.0, y: 0.0, width: 500.0, height: 500.0 };
+        let mut input = payload(Vec::new(), Vec::new());
+        input.shapes = serde_json::from_str(
+            r#"[
+                {"type": "arrow", "from": {"x": 0.0, "y": 0.5}, "to": {"x": 1.0, "y": 0.5}},
+                {"type": "badge", "at": {"x": 2000, "y": 500, "desktop": true}, "number": 1},
+                {"type": "box", "bounds": {"xMin": 0.5, "yMin": 0.0, "xMax": 1.0, "yMax": 0.5, "screenId": 1}}
+            ]"#,
+        )
+        .unwrap();
+
+        let routed = route(&input, &area, &screens);
+
+        let home = &routed[0].1;
+        assert_eq!(home.shapes.len(), 1);
+        let ends: Vec<(f64, f64)> = home.shapes[0].points().iter().map(|p| (p.x, p.y)).collect();
+        assert_eq!(ends, vec![(0.5, 0.5), (1.0, 0.5)]);
+
+        let other = &routed[1].1;
+        assert_eq!(other.shapes.len(), 2);
+        assert_eq!((other.shapes[0].points()[0].x, other.shapes[0].points()[0].y), (0.5, 0.5));
+        assert!(!other.shapes[0].points()[0].desktop);
+        assert!(matches!(other.shapes[1].kind, crate::shapes::ShapeKind::Box { .. }));
+    }
+
+    #[test]
+    fn normalizes_desktop_and_screen_items_to_a_capture_area() {
+        let screens = vec![screen(0, 0, 1000, 500), screen(1, 1000, 2000, 1000)];
+        let area = Rect { x: 500.0, y: 0.0, width: 500.0, height: 500.0 };
+        let plain = payload(vec![Point { x: 0.5, y: 0.5, ..Default::default() }], Vec::new());
+        assert!(!has_absolute_items(&plain));
+
+        let points = vec![
+            Point { x: 750.0, y: 250.0, desktop: true, ..Default::default() },
+            Point { x: 0.5, y: 0.5, screen_id: Some(0), ..Default::default() },
+        ];
+        let boxes = vec![BoundingBox { x_min: 500.0, y_min: 0.0, x_max: 750.0, y_max: 500.0, desktop: true, ..Default::default() }];
+        let input = payload(points, boxes);
+        assert!(has_absolute_items(&input));
+
+        let mapped = normalize_to(&input, &area, &screens, &area);
+        let points: Vec<(f64, f64, bool)> = mapped.points.iter().map(|p| (p.x, p.y, p.desktop)).collect();
+        assert_eq!(points, vec![(0.5, 0.5, false), (0.0, 0.5, false)]);
+        assert_eq!((mapped.boxes[0].x_min, mapped.boxes[0].x_max, mapped.boxes[0].y_max), (0.0, 0.5, 1.0));
+        assert!(!has_absolute_items(&mapped));
+    }
+
+    #[test]
+    fn masks_what_the_overlay_draws_but_not_inside_targets() {
+        let screens = vec![screen(0, 0, 1000, 500)];
+        let area = screens[0].bounds();
+        let points = vec![Point { x: 0.1, y: 0.2, ..Default::default() }];
+        let boxes = vec![BoundingBox { x_min: 0.4, y_min: 0.4, x_max: 0.8, y_max: 0.8, ..Default::default() }];
+        let rects = drawn_rects(&payload(points, boxes), &area, &screens);
+        let covered = |x: f64, y: f64| rects.iter().any(|rect| rect.contains(x, y));
+
+        assert!(covered(100.0, 100.0) && covered(140.0, 100.0));
+        assert!(covered(400.0, 300.0) && covered(600.0, 398.0));
+        assert!(!covered(600.0, 300.0) && !covered(900.0, 100.0));
+    }
+
+    #[test]
+    fn replays_the_current_step_per_screen() {
+        let screens = vec![screen(0, 0, 1000, 500), screen(1, 1000, 2000, 1000)];
+        assert!(OverlayState::default().for_screen(&screens, 0).is_none());
+
+        let points = vec![Point { x: 1500.0, y: 100.0, desktop: true, ..Default::default() }];
+        let mut state = OverlayState { hidden: true, ..Default::default() };
+        state.set_step(Rect { x: 0.0, y: 0.0, width: 1000.0, height: 500.0 }, payload(points, Vec::new()));
+        assert!(!state.hidden);
+
+        let home = state.for_screen(&screens, 0).unwrap();
+        assert_eq!(home.instruction.as_deref(), Some("Click Save"));
+        assert!(home.points.is_empty());
+        assert_eq!(state.for_screen(&screens, 1).unwrap().points.len(), 1);
+        assert!(state.for_screen(&screens, 7).is_none());
+    }
+
+    #[test]
+    fn dims_every_screen_with_holes_only_where_targets_are() {
+        let screens = vec![screen(0, 0, 1000, 500), screen(1, 1000, 2000, 1000)];
+        let area = Rect { x: 0.0, y: 0.0, width: 1000.0, height: 500.0 };
+        let mut input = payload(vec![Point { x: 0.5, y: 0.5, ..Default::default() }], Vec::new());
+        input.spotlight = Some(spotlight::Spotlight::default());
+
+        let routed = route(&input, &area, &screens);
+
+        let home = routed[0].1.spotlight.as_ref().unwrap();
+        assert_eq!(home.cutouts.len(), 1);
+        assert!(home.cutouts[0].contains(500.0, 250.0));
+        assert!(routed[1].1.spotlight.as_ref().unwrap().cutouts.is_empty());
+    }
+}
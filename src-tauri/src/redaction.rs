@@ -0,0 +1,240 @@
+use screenshots::image::imageops::{self, FilterType};
+use screenshots::image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::capture::{self, Rect, ScreenInfo};
+
+const RULES_FILE: &str = "redactions.json";
+// Blurred regions are shrunk by this factor and scaled back up, which is
+// enough to make text unreadable
+const BLUR_FACTOR: u32 = 16;
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactionStyle {
+    #[default]
+    Blackout,
+    Blur,
+}
+
+// What a rule covers
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum RedactionTarget {
+    // Fixed rectangle in logical desktop coordinates, e.g. a password manager sidebar
+    #[serde(rename_all = "camelCase")]
+    Rect { rect: Rect },
+    // Every window of an app, matched by process name (case-insensitive)
+    #[serde(rename_all = "camelCase")]
+    App { app_name: String },
+    // Region drawn by the user, normalized 0..1 to the capture area so it
+    // follows the focused window
+    #[serde(rename_all = "camelCase")]
+    Region { bounds: Rect },
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionRule {
+    pub id: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub style: RedactionStyle,
+    pub target: RedactionTarget,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+// One redaction applied to a capture, returned with it for auditing
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedRedaction {
+    pub rule_id: String,
+    pub label: Option<String>,
+    pub style: RedactionStyle,
+    // Redacted part of the capture in logical desktop coordinates
+    pub area: Rect,
+}
+
+pub fn load(app: &tauri::AppHandle) -> Vec<RedactionRule> {
+    let Ok(dir) = app.path().app_data_dir() else {
+        return Vec::new();
+    };
+
+    match std::fs::read_to_string(dir.join(RULES_FILE)) {
+        Ok(content) => parse(&content).unwrap_or_else(|e| {
+            println!("[Prism] Ignoring invalid {}: {}", RULES_FILE, e);
+            Vec::new()
+        }),
+        Err(_) => Vec::new(),
+    }
+}
+
+// Rules that would fail `validate` are dropped one by one, so a single bad
+// rule (or one this platform can't enforce) doesn't block every capture
+fn parse(content: &str) -> Result<Vec<RedactionRule>, String> {
+    let rules: Vec<RedactionRule> = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let mut kept: Vec<RedactionRule> = Vec::with_capacity(rules.len());
+    for rule in rules {
+        kept.push(rule);
+        if let Err(e) = validate(&kept) {
+            println!("[Prism] Ignoring redaction rule: {}", e);
+            kept.pop();
+        }
+    }
+    Ok(kept)
+}
+
+pub fn save(app: &tauri::AppHandle, rules: &[RedactionRule]) -> Result<(), String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let json = serde_json::to_string_pretty(rules).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(RULES_FILE), json).map_err(|e| e.to_string())
+}
+
+pub fn validate(rules: &[RedactionRule]) -> Result<(), String> {
+    for (index, rule) in rules.iter().enumerate() {
+        if rule.id.trim().is_empty() {
+            return Err(format!("Redaction rule {} has no id", index));
+        }
+        if rules[..index].iter().any(|other| other.id == rule.id) {
+            return Err(format!("Duplicate redaction rule id {}", rule.id));
+        }
+
+        match &rule.target {
+            RedactionTarget::Rect { rect } | RedactionTarget::Region { bounds: rect } => {
+                if !(rect.width > 0.0 && rect.height > 0.0) {
+                    return Err(format!("Redaction rule {} has an empty rectangle", rule.id));
+                }
+            }
+            RedactionTarget::App { app_name } => {
+                if app_name.trim().is_empty() {
+                    return Err(format!("Redaction rule {} has no app name", rule.id));
+                }
+                // Rules we cannot enforce must not look like they are active
+                if cfg!(not(target_os = "macos")) {
+                    return Err("App redaction rules need window listing, which is only supported on macOS".to_string());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Apps whose windows need to be looked up before capturing
+pub fn app_names(rules: &[RedactionRule]) -> Vec<String> {
+    rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .filter_map(|rule| match &rule.target {
+            RedactionTarget::App { app_name } => Some(app_name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+// Turn the enabled rules into desktop rectangles inside the capture area.
+// `app_windows` holds (app name, window frame) for the apps named in the rules.
+pub fn resolve(
+    rules: &[RedactionRule],
+    area: &Rect,
+    app_windows: &[(String, Rect)],
+) -> Vec<AppliedRedaction> {
+    let mut redactions = Vec::new();
+
+    for rule in rules.iter().filter(|rule| rule.enabled) {
+        let rects: Vec<Rect> = match &rule.target {
+            RedactionTarget::Rect { rect } => vec![*rect],
+            RedactionTarget::App { app_name } => app_windows
+                .iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case(app_name))
+                .map(|(_, frame)| *frame)
+                .collect(),
+            RedactionTarget::Region { bounds } => vec![Rect {
+                x: area.x + bounds.x * area.width,
+                y: area.y + bounds.y * area.height,
+                width: bounds.width * area.width,
+                height: bounds.height * area.height,
+            }],
+        };
+
+        redactions.extend(rects.iter().filter_map(|rect| rect.intersect(area)).map(|rect| {
+            AppliedRedaction {
+                rule_id: rule.id.clone(),
+                label: rule.label.clone(),
+                style: rule.style,
+                area: rect,
+            }
+        }));
+    }
+
+    redactions
+}
+
+// Redact a full-screen capture of `screen` in place
+pub fn apply(image: &mut RgbaImage, screen: &ScreenInfo, redactions: &[AppliedRedaction]) {
+    for redaction in redactions {
+        match redaction.style {
+            RedactionStyle::Blackout => {
+                capture::mask_area(image, &redaction.area, screen);
+            }
+            RedactionStyle::Blur => blur_area(image, &redaction.area, screen),
+        }
+    }
+}
+
+fn blur_area(image: &mut RgbaImage, area: &Rect, screen: &ScreenInfo) {
+    let Some(visible) = area.intersect(&screen.bounds()) else {
+        return;
+    };
+
+    let pixels = capture::physical_crop(&visible, screen, image.width(), image.height());
+    if pixels.width == 0 || pixels.height == 0 {
+        return;
+    }
+
+    let region = imageops::crop_imm(image, pixels.x, pixels.y, pixels.width, pixels.height).to_image();
+    let small = imageops::resize(
+        &region,
+        (pixels.width / BLUR_FACTOR).max(1),
+        (pixels.height / BLUR_FACTOR).max(1),
+        FilterType::Triangle,
+    );
+    let mut blurred = imageops::resize(&small, pixels.width, pixels.height, FilterType::Triangle);
+
+    // Keep the result opaque so nothing underneath can be recovered from alpha
+    for pixel in blurred.pixels_mut() {
+        *pixel = Rgba([pixel[0], pixel[1], pixel[2], 255]);
+    }
+
+    imageops::replace(image, &blurred, pixels.x as i64, pixels.y as i64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_invalid_rules_and_keeps_the_rest() {
+        let rules = parse(
+            r#"[
+                {"id": "a", "target": {"kind": "rect", "rect": {"x": 0, "y": 0, "width": 10, "height": 10}}},
+                {"id": "b", "target": {"kind": "rect", "rect": {"x": 0, "y": 0, "width": 0, "height": 10}}},
+                {"id": "a", "target": {"kind": "region", "bounds": {"x": 0.5, "y": 0.5, "width": 0.2, "height": 0.2}}},
+                {"id": "c", "target": {"kind": "region", "bounds": {"x": 0.5, "y": 0.5, "width": 0.2, "height": 0.2}}}
+            ]"#,
+        )
+        .unwrap();
+        let ids: Vec<&str> = rules.iter().map(|rule| rule.id.as_str()).collect();
+        assert_eq!(ids, ["a", "c"]);
+        assert!(parse("not json").is_err());
+    }
+}
@@ -0,0 +1,624 @@
+use font8x8::{UnicodeFonts, BASIC_FONTS, LATIN_FONTS};
+use screenshots::image::{Rgba, RgbaImage};
+
+use crate::capture::Rect;
+use crate::cursor::inside;
+use crate::shapes::{self, Shape, ShapeKind};
+use crate::spotlight::{self, Spotlight};
+use crate::theme::OverlayStyle;
+use crate::OverlayPayload;
+
+// Colors and sizes follow ScreenOverlay.tsx so exports look like the live overlay.
+// Marker colors come from the payload's theme style. Sizes are in logical
+// pixels and multiplied by the render scale.
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const PANEL: Rgba<u8> = Rgba([0, 0, 0, 204]);
+const PANEL_BORDER: Rgba<u8> = Rgba([168, 85, 247, 128]);
+const PANEL_BORDER_COMPLETE: Rgba<u8> = Rgba([34, 197, 94, 179]);
+const STEP_TEXT: Rgba<u8> = Rgba([216, 180, 254, 255]);
+const COMPLETE_TEXT: Rgba<u8> = Rgba([74, 222, 128, 255]);
+
+const POINT_RADIUS: f64 = 16.0;
+const POINT_BORDER: f64 = 4.0;
+const LABEL_PADDING: f64 = 6.0;
+const PANEL_MARGIN: f64 = 32.0;
+const PANEL_PADDING: f64 = 20.0;
+const PANEL_MAX_WIDTH: f64 = 448.0;
+// Glyphs are 8x8; text is drawn at this many logical pixels per glyph pixel
+const TEXT_SCALE: f64 = 2.0;
+const BADGE_RADIUS: f64 = 14.0;
+const DASH: f64 = 3.0;
+const GAP: f64 = 2.0;
+
+// Theme style as pixel colors
+struct Palette {
+    point: Rgba<u8>,
+    area: Rgba<u8>,
+    text: Rgba<u8>,
+    stroke: f64,
+    fill_opacity: f64,
+}
+
+impl Palette {
+    fn new(style: &OverlayStyle) -> Self {
+        // Styles are validated on the way in; fall back to white all the same
+        let color = |hex: &str| {
+            let [r, g, b] = shapes::parse_color(hex).unwrap_or([255, 255, 255]);
+            Rgba([r, g, b, 255])
+        };
+        Self {
+            point: color(&style.point_color),
+            area: color(&style.box_color),
+            text: color(&style.label_text_color),
+            stroke: style.stroke_width,
+            fill_opacity: style.fill_opacity,
+        }
+    }
+
+    fn fill(&self, color: Rgba<u8>, opacity: f64) -> Rgba<u8> {
+        Rgba([color[0], color[1], color[2], (opacity * 255.0).round() as u8])
+    }
+}
+
+// Draw points, boxes, their caption and the walkthrough panel onto `image`.
+// Coordinates in `payload` are normalized 0..1 to the image. `scale` is image
+// pixels per logical pixel of the capture.
+pub fn annotate(image: &mut RgbaImage, payload: &OverlayPayload, scale: f64) {
+    let scale = scale.max(0.25);
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    let single = payload.points.len() + payload.boxes.len() == 1;
+    let palette = Palette::new(&payload.style);
+
+    // Dim first so markers stay bright on top of the mask
+    if let Some(spotlight) = &payload.spotlight {
+        draw_spotlight(image, payload, spotlight, scale);
+    }
+
+    for bbox in &payload.boxes {
+        let x0 = bbox.x_min * width;
+        let y0 = bbox.y_min * height;
+        let x1 = bbox.x_max * width;
+        let y1 = bbox.y_max * height;
+
+        fill_rect(image, x0, y0, x1, y1, palette.fill(palette.area, palette.fill_opacity));
+        stroke_rect(image, x0, y0, x1, y1, palette.stroke * scale, palette.area);
+
+        if let Some(caption) = &payload.caption {
+            let label_height = text_height(scale) + 2.0 * LABEL_PADDING * scale;
+            draw_label(image, x0, y0 - label_height - 4.0 * scale, caption, (palette.area, palette.text), scale);
+        }
+    }
+
+    for (index, point) in payload.points.iter().enumerate() {
+        let cx = point.x * width;
+        let cy = point.y * height;
+        let radius = POINT_RADIUS * scale;
+
+        fill_circle(image, cx, cy, radius, WHITE);
+        fill_circle(image, cx, cy, radius - POINT_BORDER * scale, palette.point);
+
+        // Number the markers when there is more than one, otherwise use the step number
+        let number = if single {
+            payload.current_step.unwrap_or(1)
+        } else {
+            index as u32 + 1
+        };
+        let digits = number.to_string();
+        let text_scale = scale * 0.75;
+        draw_text(
+            image,
+            cx - text_width(&digits, text_scale) / 2.0,
+            cy - text_height(text_scale) / 2.0,
+            &digits,
+            palette.text,
+            text_scale,
+        );
+
+        if let Some(caption) = &payload.caption {
+            let label_width = text_width(caption, scale) + 2.0 * LABEL_PADDING * scale;
+            let label_height = text_height(scale) + 2.0 * LABEL_PADDING * scale;
+            draw_label(
+                image,
+                cx - label_width / 2.0,
+                cy - radius - label_height - 6.0 * scale,
+                caption,
+                (palette.point, palette.text),
+                scale,
+            );
+        }
+    }
+
+    for shape in &payload.shapes {
+        draw_shape(image, shape, &palette, scale);
+    }
+
+    if payload.walkthrough_steps.is_some_and(|steps| steps > 1) {
+        draw_panel(image, payload, scale);
+    }
+}
+
+fn draw_spotlight(image: &mut RgbaImage, payload: &OverlayPayload, spotlight: &Spotlight, scale: f64) {
+    let cutouts = spotlight::cutouts(payload, spotlight, image.width() as f64 / scale, image.height() as f64 / scale);
+    let shade = Rgba([0, 0, 0, (spotlight.opacity * 255.0).round() as u8]);
+
+    for y in 0..image.height() {
+        for x in 0..image.width() {
+            let (lx, ly) = ((x as f64 + 0.5) / scale, (y as f64 + 0.5) / scale);
+            if !cutouts.iter().any(|c| c.contains(lx, ly)) {
+                blend(image, x as i64, y as i64, shade, 1.0);
+            }
+        }
+    }
+}
+
+// Defaults match the legacy markers: the point color for pointing, the box
+// color for areas
+fn shape_color(shape: &Shape, palette: &Palette) -> Rgba<u8> {
+    let fallback = match shape.kind {
+        ShapeKind::Box { .. } | ShapeKind::Polygon { .. } => palette.area,
+        ShapeKind::Text { .. } => PANEL,
+        _ => palette.point,
+    };
+    match shape.style.color.as_deref().and_then(shapes::parse_color) {
+        Some([r, g, b]) => Rgba([r, g, b, 255]),
+        None => fallback,
+    }
+}
+
+fn draw_shape(image: &mut RgbaImage, shape: &Shape, palette: &Palette, scale: f64) {
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    let color = shape_color(shape, palette);
+    let stroke = shape.style.stroke_width.unwrap_or(palette.stroke) * scale;
+    let dashed = shape.style.dashed;
+    let fill = palette.fill(color, shape.style.fill_opacity.unwrap_or(palette.fill_opacity));
+    let pixels: Vec<(f64, f64)> = shape.points().iter().map(|p| (p.x * width, p.y * height)).collect();
+
+    // Labels sit above the shape's topmost point
+    let anchor = match &shape.kind {
+        ShapeKind::Point { .. } => {
+            let (cx, cy) = pixels[0];
+            let radius = POINT_RADIUS * scale;
+            fill_circle(image, cx, cy, radius, WHITE);
+            fill_circle(image, cx, cy, radius - POINT_BORDER * scale, color);
+            (cx, cy - radius)
+        }
+        ShapeKind::Box { bounds } => {
+            let corners = [
+                (bounds.x_min * width, bounds.y_min * height),
+                (bounds.x_max * width, bounds.y_min * height),
+                (bounds.x_max * width, bounds.y_max * height),
+                (bounds.x_min * width, bounds.y_max * height),
+            ];
+            fill_polygon(image, &corners, fill);
+            stroke_path(image, &corners, true, stroke, dashed, color);
+            ((corners[0].0 + corners[1].0) / 2.0, corners[0].1)
+        }
+        ShapeKind::Arrow { .. } => {
+            let (from, to) = (pixels[0], pixels[1]);
+            let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+            let length = (dx * dx + dy * dy).sqrt().max(1.0);
+            let (ux, uy) = (dx / length, dy / length);
+            let head = (stroke * 4.0).max(12.0 * scale).min(length);
+            let base = (to.0 - ux * head, to.1 - uy * head);
+
+            stroke_path(image, &[from, base], false, stroke, dashed, color);
+            fill_polygon(
+                image,
+                &[
+                    to,
+                    (base.0 - uy * head / 2.0, base.1 + ux * head / 2.0),
+                    (base.0 + uy * head / 2.0, base.1 - ux * head / 2.0),
+                ],
+                color,
+            );
+            top(&pixels)
+        }
+        ShapeKind::Polyline { .. } => {
+            stroke_path(image, &pixels, false, stroke, dashed, color);
+            top(&pixels)
+        }
+        ShapeKind::Polygon { .. } => {
+            fill_polygon(image, &pixels, fill);
+            stroke_path(image, &pixels, true, stroke, dashed, color);
+            top(&pixels)
+        }
+        ShapeKind::Text { text, .. } => {
+            let (x, y) = pixels[0];
+            draw_label(image, x, y, text, (color, WHITE), scale);
+            (x, y)
+        }
+        ShapeKind::Badge { number, .. } => {
+            let (cx, cy) = pixels[0];
+            let radius = BADGE_RADIUS * scale;
+            fill_circle(image, cx, cy, radius, color);
+            let digits = number.to_string();
+            let text_scale = scale * 0.75;
+            draw_text(
+                image,
+                cx - text_width(&digits, text_scale) / 2.0,
+                cy - text_height(text_scale) / 2.0,
+                &digits,
+                palette.text,
+                text_scale,
+            );
+            (cx, cy - radius)
+        }
+    };
+
+    if let Some(label) = &shape.label {
+        let label_width = text_width(label, scale) + 2.0 * LABEL_PADDING * scale;
+        let label_height = text_height(scale) + 2.0 * LABEL_PADDING * scale;
+        let colors = if matches!(shape.kind, ShapeKind::Text { .. }) { (PANEL, WHITE) } else { (color, palette.text) };
+        draw_label(
+            image,
+            anchor.0 - label_width / 2.0,
+            anchor.1 - label_height - 6.0 * scale,
+            label,
+            colors,
+            scale,
+        );
+    }
+}
+
+fn top(points: &[(f64, f64)]) -> (f64, f64) {
+    points
+        .iter()
+        .copied()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or_default()
+}
+
+// Step counter and instruction in the top-left corner
+fn draw_panel(image: &mut RgbaImage, payload: &OverlayPayload, scale: f64) {
+    let complete = payload.is_complete.unwrap_or(false);
+    let title = if complete {
+        "Walkthrough Complete!".to_string()
+    } else {
+        format!(
+            "Step {} of {}",
+            payload.current_step.unwrap_or(1),
+            payload.walkthrough_steps.unwrap_or(1)
+        )
+    };
+
+    let padding = PANEL_PADDING * scale;
+    let line_height = text_height(scale) * 1.5;
+    let max_text_width = (PANEL_MAX_WIDTH * scale - 2.0 * padding)
+        .min(image.width() as f64 - 2.0 * (PANEL_MARGIN * scale + padding));
+    let lines = payload
+        .instruction
+        .as_deref()
+        .map(|text| wrap(text, max_text_width, scale))
+        .unwrap_or_default();
+
+    let content_width = lines
+        .iter()
+        .map(|line| text_width(line, scale))
+        .fold(text_width(&title, scale), f64::max);
+    let x0 = PANEL_MARGIN * scale;
+    let y0 = PANEL_MARGIN * scale;
+    let x1 = x0 + content_width + 2.0 * padding;
+    let y1 = y0 + 2.0 * padding + line_height * (1 + lines.len()) as f64;
+
+    fill_rect(image, x0, y0, x1, y1, PANEL);
+    let border = if complete { PANEL_BORDER_COMPLETE } else { PANEL_BORDER };
+    stroke_rect(image, x0, y0, x1, y1, 2.0 * scale, border);
+
+    let title_color = if complete { COMPLETE_TEXT } else { STEP_TEXT };
+    draw_text(image, x0 + padding, y0 + padding, &title, title_color, scale);
+    for (index, line) in lines.iter().enumerate() {
+        let y = y0 + padding + line_height * (index + 1) as f64;
+        draw_text(image, x0 + padding, y, line, WHITE, scale);
+    }
+}
+
+// Greedy word wrap to a maximum width in pixels
+fn wrap(text: &str, max_width: f64, scale: f64) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+
+        if text_width(&candidate, scale) <= max_width || line.is_empty() {
+            line = candidate;
+        } else {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        }
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+// `colors` are the background and the text color
+fn draw_label(image: &mut RgbaImage, x: f64, y: f64, text: &str, colors: (Rgba<u8>, Rgba<u8>), scale: f64) {
+    let (background, foreground) = colors;
+    let padding = LABEL_PADDING * scale;
+    let width = text_width(text, scale) + 2.0 * padding;
+    let height = text_height(scale) + 2.0 * padding;
+
+    // Keep labels inside the image
+    let x = x.clamp(0.0, (image.width() as f64 - width).max(0.0));
+    let y = y.clamp(0.0, (image.height() as f64 - height).max(0.0));
+
+    fill_rect(image, x, y, x + width, y + height, background);
+    draw_text(image, x + padding, y + padding, text, foreground, scale);
+}
+
+fn glyph_size(scale: f64) -> f64 {
+    (TEXT_SCALE * scale).round().max(1.0)
+}
+
+fn text_width(text: &str, scale: f64) -> f64 {
+    text.chars().count() as f64 * 8.0 * glyph_size(scale)
+}
+
+fn text_height(scale: f64) -> f64 {
+    8.0 * glyph_size(scale)
+}
+
+// 8x8 bitmap glyphs scaled up by whole pixels; unknown characters become '?'
+fn draw_text(image: &mut RgbaImage, x: f64, y: f64, text: &str, color: Rgba<u8>, scale: f64) {
+    let size = glyph_size(scale) as i64;
+    let (x, y) = (x.round() as i64, y.round() as i64);
+
+    for (index, c) in text.chars().enumerate() {
+        let glyph = BASIC_FONTS
+            .get(c)
+            .or_else(|| LATIN_FONTS.get(c))
+            .or_else(|| BASIC_FONTS.get('?'))
+            .unwrap_or([0; 8]);
+        let origin = x + index as i64 * 8 * size;
+
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..8 {
+                if bits >> col & 1 == 0 {
+                    continue;
+                }
+                let px = origin + col * size;
+                let py = y + row as i64 * size;
+                fill_rect(image, px as f64, py as f64, (px + size) as f64, (py + size) as f64, color);
+            }
+        }
+    }
+}
+
+// Alpha-blend `color` onto a pixel with extra coverage 0..1 for antialiasing
+fn blend(image: &mut RgbaImage, x: i64, y: i64, color: Rgba<u8>, coverage: f64) {
+    if x < 0 || y < 0 || x >= image.width() as i64 || y >= image.height() as i64 {
+        return;
+    }
+
+    let alpha = color[3] as f64 / 255.0 * coverage.clamp(0.0, 1.0);
+    if alpha <= 0.0 {
+        return;
+    }
+
+    let pixel = image.get_pixel_mut(x as u32, y as u32);
+    for channel in 0..3 {
+        let mixed = color[channel] as f64 * alpha + pixel[channel] as f64 * (1.0 - alpha);
+        pixel[channel] = mixed.round() as u8;
+    }
+    pixel[3] = (alpha * 255.0 + pixel[3] as f64 * (1.0 - alpha)).round() as u8;
+}
+
+fn fill_rect(image: &mut RgbaImage, x0: f64, y0: f64, x1: f64, y1: f64, color: Rgba<u8>) {
+    let (x0, x1) = (x0.min(x1).round() as i64, x0.max(x1).round() as i64);
+    let (y0, y1) = (y0.min(y1).round() as i64, y0.max(y1).round() as i64);
+    let x0 = x0.max(0);
+    let y0 = y0.max(0);
+    let x1 = x1.min(image.width() as i64);
+    let y1 = y1.min(image.height() as i64);
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            blend(image, x, y, color, 1.0);
+        }
+    }
+}
+
+// Border drawn inside the rectangle, like a CSS border
+fn stroke_rect(image: &mut RgbaImage, x0: f64, y0: f64, x1: f64, y1: f64, thickness: f64, color: Rgba<u8>) {
+    let t = thickness.min((x1 - x0) / 2.0).min((y1 - y0) / 2.0).max(1.0);
+    fill_rect(image, x0, y0, x1, y0 + t, color);
+    fill_rect(image, x0, y1 - t, x1, y1, color);
+    fill_rect(image, x0, y0 + t, x0 + t, y1 - t, color);
+    fill_rect(image, x1 - t, y0 + t, x1, y1 - t, color);
+}
+
+fn fill_circle(image: &mut RgbaImage, cx: f64, cy: f64, radius: f64, color: Rgba<u8>) {
+    if radius <= 0.0 {
+        return;
+    }
+
+    let (x0, x1) = ((cx - radius).floor() as i64, (cx + radius).ceil() as i64);
+    let (y0, y1) = ((cy - radius).floor() as i64, (cy + radius).ceil() as i64);
+
+    for y in y0..=y1 {
+        for x in x0..=x1 {
+            let dx = x as f64 + 0.5 - cx;
+            let dy = y as f64 + 0.5 - cy;
+            let distance = (dx * dx + dy * dy).sqrt();
+            // One pixel of falloff at the edge
+            blend(image, x, y, color, radius + 0.5 - distance);
+        }
+    }
+}
+
+fn fill_polygon(image: &mut RgbaImage, polygon: &[(f64, f64)], color: Rgba<u8>) {
+    if polygon.len() < 3 {
+        return;
+    }
+
+    let bounds = Rect::enclosing(polygon);
+    let x0 = bounds.x.floor().max(0.0) as i64;
+    let y0 = bounds.y.floor().max(0.0) as i64;
+    let x1 = ((bounds.x + bounds.width).ceil() as i64).min(image.width() as i64);
+    let y1 = ((bounds.y + bounds.height).ceil() as i64).min(image.height() as i64);
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            if inside(polygon, x as f64 + 0.5, y as f64 + 0.5) {
+                blend(image, x, y, color, 1.0);
+            }
+        }
+    }
+}
+
+// Connected line segments; dashes restart at every vertex
+fn stroke_path(image: &mut RgbaImage, path: &[(f64, f64)], closed: bool, thickness: f64, dashed: bool, color: Rgba<u8>) {
+    let closing = if closed { path.last().zip(path.first()) } else { None };
+    let segments = path.windows(2).map(|pair| (pair[0], pair[1])).chain(closing.map(|(a, b)| (*a, *b)));
+
+    for (a, b) in segments {
+        if !dashed {
+            stroke_segment(image, a, b, thickness, color);
+            continue;
+        }
+
+        let length = ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+        let (dash, period) = (DASH * thickness, (DASH + GAP) * thickness);
+        let at = |t: f64| (a.0 + (b.0 - a.0) * t / length, a.1 + (b.1 - a.1) * t / length);
+        let mut start = 0.0;
+        while start < length {
+            stroke_segment(image, at(start), at((start + dash).min(length)), thickness, color);
+            start += period;
+        }
+    }
+}
+
+// Antialiased line with flat ends, `thickness` pixels wide
+fn stroke_segment(image: &mut RgbaImage, a: (f64, f64), b: (f64, f64), thickness: f64, color: Rgba<u8>) {
+    let half = thickness / 2.0;
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sq = (dx * dx + dy * dy).max(f64::EPSILON);
+
+    let x0 = (a.0.min(b.0) - half).floor() as i64;
+    let y0 = (a.1.min(b.1) - half).floor() as i64;
+    let x1 = (a.0.max(b.0) + half).ceil() as i64;
+    let y1 = (a.1.max(b.1) + half).ceil() as i64;
+
+    for y in y0.max(0)..=y1.min(image.height() as i64) {
+        for x in x0.max(0)..=x1.min(image.width() as i64) {
+            let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+            let t = ((px - a.0) * dx + (py - a.1) * dy) / length_sq;
+            if !(0.0..=1.0).contains(&t) {
+                continue;
+            }
+            let distance = ((a.0 + t * dx - px).powi(2) + (a.1 + t * dy - py).powi(2)).sqrt();
+            blend(image, x, y, color, half + 0.5 - distance);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, Point};
+
+    fn payload() -> OverlayPayload {
+        OverlayPayload {
+            points: vec![Point { x: 0.25, y: 0.5, ..Default::default() }],
+            boxes: vec![BoundingBox { x_min: 0.5, y_min: 0.25, x_max: 0.9, y_max: 0.75, ..Default::default() }],
+            walkthrough_steps: Some(3),
+            current_step: Some(2),
+            instruction: Some("Click the Save button in the toolbar".to_string()),
+            caption: Some("Save".to_string()),
+            is_complete: Some(false),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn draws_markers_boxes_and_panel() {
+        let mut image = RgbaImage::from_pixel(800, 400, Rgba([255, 255, 255, 255]));
+        annotate(&mut image, &payload(), 1.0);
+        let palette = Palette::new(&OverlayStyle::default());
+
+        // Point marker: red inside the white border
+        let marker = image.get_pixel(200, 210);
+        assert_eq!((marker[0], marker[1], marker[2]), (palette.point[0], palette.point[1], palette.point[2]));
+        // Box border is solid green, its inside only tinted
+        assert_eq!(*image.get_pixel(400 + 1, 200), palette.area);
+        let inside = image.get_pixel(560, 280);
+        assert!(inside[1] > inside[0] && inside[0] > 200);
+        // Panel background in the top-left corner
+        assert!(image.get_pixel(40, 40)[0] < 80);
+        // Untouched area stays white
+        assert_eq!(*image.get_pixel(790, 390), WHITE);
+    }
+
+    #[test]
+    fn draws_shapes_with_style_hints() {
+        let mut input = payload();
+        input.points.clear();
+        input.boxes.clear();
+        input.walkthrough_steps = None;
+        input.shapes = serde_json::from_str(
+            r##"[
+                {"type": "arrow", "from": {"x": 0.1, "y": 0.5}, "to": {"x": 0.4, "y": 0.5}},
+                {"type": "polygon", "points": [{"x": 0.5, "y": 0.2}, {"x": 0.9, "y": 0.2}, {"x": 0.7, "y": 0.8}],
+                 "style": {"color": "#0000ff", "fillOpacity": 1.0}},
+                {"type": "badge", "at": {"x": 0.1, "y": 0.9}, "number": 3}
+            ]"##,
+        )
+        .unwrap();
+
+        let mut image = RgbaImage::from_pixel(800, 400, Rgba([255, 255, 255, 255]));
+        annotate(&mut image, &input, 1.0);
+        let red = Palette::new(&OverlayStyle::default()).point;
+
+        // Arrow shaft and head
+        assert_eq!(*image.get_pixel(200, 200), red);
+        assert_eq!(*image.get_pixel(315, 200), red);
+        // Polygon filled with its own color
+        assert_eq!(*image.get_pixel(560, 200), Rgba([0, 0, 255, 255]));
+        // Badge
+        assert_eq!(*image.get_pixel(80, 348), red);
+        assert_eq!(*image.get_pixel(790, 390), WHITE);
+    }
+
+    #[test]
+    fn draws_markers_in_the_theme_colors() {
+        let mut input = payload();
+        input.style = crate::theme::Preset::ColorBlindSafe.style();
+
+        let mut image = RgbaImage::from_pixel(800, 400, Rgba([255, 255, 255, 255]));
+        annotate(&mut image, &input, 1.0);
+
+        assert_eq!(*image.get_pixel(200, 210), Rgba([213, 94, 0, 255]));
+        assert_eq!(*image.get_pixel(400 + 1, 200), Rgba([0, 114, 178, 255]));
+    }
+
+    #[test]
+    fn dims_everything_but_the_targets() {
+        let mut input = payload();
+        input.walkthrough_steps = None;
+        input.caption = None;
+        input.spotlight = Some(Spotlight::default());
+
+        let mut image = RgbaImage::from_pixel(800, 400, Rgba([255, 255, 255, 255]));
+        annotate(&mut image, &input, 1.0);
+
+        // Inside the padded box hole the tint is the box fill, not the mask
+        assert!(image.get_pixel(560, 280)[0] > 200);
+        assert!(image.get_pixel(395, 200)[0] > 200);
+        // Around the point marker, inside its round hole
+        assert_eq!(*image.get_pixel(200, 228), WHITE);
+        // Far from any target
+        assert_eq!(image.get_pixel(790, 390)[0], 102);
+    }
+
+    #[test]
+    fn wraps_long_instructions() {
+        let lines = wrap("one two three four five", text_width("one two", 1.0), 1.0);
+        assert_eq!(lines, vec!["one two", "three", "four", "five"]);
+    }
+}
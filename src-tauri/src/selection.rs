@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::{Listener, WebviewWindow, WindowEvent};
+
+use crate::capture::{CaptureResult, Rect};
+
+// Event the selection overlay emits with the dragged rectangle, or null when cancelled
+pub const SELECTED_EVENT: &str = "region-selected";
+// Drags smaller than this (logical px) are treated as accidental clicks
+const MIN_REGION_SIZE: f64 = 8.0;
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SelectedRegion {
+    // Normalized 0..1 to the selection overlay
+    bounds: Rect,
+}
+
+// Result of start_region_selection
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionCapture {
+    // Selected rectangle in logical desktop coordinates
+    pub rect: Rect,
+    // Capture of just that rectangle
+    pub capture: CaptureResult,
+}
+
+// Wait until the user finishes dragging on the selection overlay. Returns the
+// normalized bounds, or None if they cancelled or the window went away.
+pub async fn wait_for_region(window: &WebviewWindow) -> Option<Rect> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let tx = Arc::new(Mutex::new(Some(tx)));
+
+    let selected_tx = tx.clone();
+    let handler = window.once(SELECTED_EVENT, move |event| {
+        let region = serde_json::from_str::<Option<SelectedRegion>>(event.payload())
+            .ok()
+            .flatten()
+            .map(|selected| selected.bounds);
+        if let Some(tx) = selected_tx.lock().unwrap().take() {
+            let _ = tx.send(region);
+        }
+    });
+
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            if let Some(tx) = tx.lock().unwrap().take() {
+                let _ = tx.send(None);
+            }
+        }
+    });
+
+    let region = rx.await.ok().flatten();
+    window.unlisten(handler);
+    region
+}
+
+// Map normalized overlay bounds onto the desktop area the overlay covered
+pub fn to_desktop(bounds: &Rect, overlay: &Rect) -> Option<Rect> {
+    let rect = Rect {
+        x: overlay.x + bounds.x * overlay.width,
+        y: overlay.y + bounds.y * overlay.height,
+        width: bounds.width * overlay.width,
+        height: bounds.height * overlay.height,
+    }
+    .intersect(overlay)?;
+
+    (rect.width >= MIN_REGION_SIZE && rect.height >= MIN_REGION_SIZE).then_some(rect)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_selection_to_desktop_and_ignores_clicks() {
+        let overlay = Rect { x: 1440.0, y: 0.0, width: 1920.0, height: 1080.0 };
+
+        let rect = to_desktop(&Rect { x: 0.25, y: 0.5, width: 0.5, height: 0.6 }, &overlay).unwrap();
+        assert_eq!(rect, Rect { x: 1920.0, y: 540.0, width: 960.0, height: 540.0 });
+
+        assert!(to_desktop(&Rect { x: 0.5, y: 0.5, width: 0.001, height: 0.001 }, &overlay).is_none());
+    }
+}
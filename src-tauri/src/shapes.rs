@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{BoundingBox, Point};
+
+// Bump when shapes change in a way older overlays can't render
+pub const PROTOCOL_VERSION: u32 = 1;
+const MAX_SHAPES: usize = 200;
+const MAX_POINTS: usize = 256;
+const MAX_TEXT_LEN: usize = 500;
+// Thickest stroke a shape or overlay style may ask for, in logical pixels
+pub const MAX_STROKE_WIDTH: f64 = 32.0;
+// Model coordinates often land a hair outside the capture
+const NORMALIZED_SLACK: f64 = 0.01;
+
+// Optional rendering hints; the overlay picks defaults per shape type
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShapeStyle {
+    // CSS hex color, #rgb or #rrggbb
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    // Logical pixels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stroke_width: Option<f64>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub dashed: bool,
+    // 0..1, for boxes and polygons
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fill_opacity: Option<f64>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ShapeKind {
+    Point { at: Point },
+    Box { bounds: BoundingBox },
+    // "Drag from here to there"
+    Arrow { from: Point, to: Point },
+    Polyline { points: Vec<Point> },
+    Polygon { points: Vec<Point> },
+    // Free-standing text, e.g. "type your email here"
+    Text { at: Point, text: String },
+    // Numbered circle marking the order of actions
+    Badge { at: Point, number: u32 },
+}
+
+// One overlay shape. Coordinates follow the Point/BoundingBox rules: normalized
+// to the capture area unless they name a screen or are in desktop pixels.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Shape {
+    #[serde(flatten)]
+    pub kind: ShapeKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub style: ShapeStyle,
+}
+
+impl ShapeKind {
+    fn name(&self) -> &'static str {
+        match self {
+            ShapeKind::Point { .. } => "point",
+            ShapeKind::Box { .. } => "box",
+            ShapeKind::Arrow { .. } => "arrow",
+            ShapeKind::Polyline { .. } => "polyline",
+            ShapeKind::Polygon { .. } => "polygon",
+            ShapeKind::Text { .. } => "text",
+            ShapeKind::Badge { .. } => "badge",
+        }
+    }
+}
+
+impl Shape {
+    // Every point of the shape; a box contributes none (see `bounds`)
+    pub fn points(&self) -> Vec<&Point> {
+        match &self.kind {
+            ShapeKind::Point { at } | ShapeKind::Text { at, .. } | ShapeKind::Badge { at, .. } => vec![at],
+            ShapeKind::Arrow { from, to } => vec![from, to],
+            ShapeKind::Polyline { points } | ShapeKind::Polygon { points } => points.iter().collect(),
+            ShapeKind::Box { .. } => Vec::new(),
+        }
+    }
+
+    pub fn points_mut(&mut self) -> Vec<&mut Point> {
+        match &mut self.kind {
+            ShapeKind::Point { at } | ShapeKind::Text { at, .. } | ShapeKind::Badge { at, .. } => vec![at],
+            ShapeKind::Arrow { from, to } => vec![from, to],
+            ShapeKind::Polyline { points } | ShapeKind::Polygon { points } => points.iter_mut().collect(),
+            ShapeKind::Box { .. } => Vec::new(),
+        }
+    }
+
+    pub fn bounds_mut(&mut self) -> Option<&mut BoundingBox> {
+        match &mut self.kind {
+            ShapeKind::Box { bounds } => Some(bounds),
+            _ => None,
+        }
+    }
+}
+
+fn check_version(version: Option<u32>) -> Result<(), String> {
+    match version {
+        Some(version) if version > PROTOCOL_VERSION => Err(format!(
+            "Unsupported shape protocol version {} (this build supports up to {})",
+            version, PROTOCOL_VERSION
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn check_coordinate(value: f64, desktop: bool) -> bool {
+    value.is_finite() && (desktop || (-NORMALIZED_SLACK..=1.0 + NORMALIZED_SLACK).contains(&value))
+}
+
+// RGB of a #rgb or #rrggbb color
+pub fn parse_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+
+    match hex.len() {
+        3 => {
+            let mut rgb = [0; 3];
+            for (index, c) in hex.chars().enumerate() {
+                rgb[index] = channel(&c.to_string())? * 17;
+            }
+            Some(rgb)
+        }
+        6 => Some([channel(&hex[0..2])?, channel(&hex[2..4])?, channel(&hex[4..6])?]),
+        _ => None,
+    }
+}
+
+fn validate(shapes: &[Shape]) -> Result<(), String> {
+    if shapes.len() > MAX_SHAPES {
+        return Err(format!("Too many shapes ({}, at most {})", shapes.len(), MAX_SHAPES));
+    }
+
+    for (index, shape) in shapes.iter().enumerate() {
+        let fail = |reason: &str| Err(format!("Shape {} ({}) {}", index, shape.kind.name(), reason));
+
+        match &shape.kind {
+            ShapeKind::Polyline { points } if points.len() < 2 => return fail("needs at least 2 points"),
+            ShapeKind::Polygon { points } if points.len() < 3 => return fail("needs at least 3 points"),
+            ShapeKind::Polyline { points } | ShapeKind::Polygon { points } if points.len() > MAX_POINTS => {
+                return fail(&format!("has more than {} points", MAX_POINTS));
+            }
+            ShapeKind::Text { text, .. } if text.trim().is_empty() => return fail("has no text"),
+            ShapeKind::Text { text, .. } if text.chars().count() > MAX_TEXT_LEN => {
+                return fail(&format!("text is longer than {} characters", MAX_TEXT_LEN));
+            }
+            ShapeKind::Badge { number: 0, .. } => return fail("numbers start at 1"),
+            ShapeKind::Box { bounds } => {
+                let corners = [bounds.x_min, bounds.y_min, bounds.x_max, bounds.y_max];
+                if !corners.iter().all(|v| check_coordinate(*v, bounds.desktop)) {
+                    return fail("has coordinates out of range");
+                }
+                if bounds.x_max <= bounds.x_min || bounds.y_max <= bounds.y_min {
+                    return fail("is empty");
+                }
+            }
+            _ => {}
+        }
+
+        if !shape.points().iter().all(|p| check_coordinate(p.x, p.desktop) && check_coordinate(p.y, p.desktop)) {
+            return fail("has coordinates out of range");
+        }
+        if shape.label.as_ref().is_some_and(|label| label.chars().count() > MAX_TEXT_LEN) {
+            return fail(&format!("label is longer than {} characters", MAX_TEXT_LEN));
+        }
+
+        let style = &shape.style;
+        if style.color.as_deref().is_some_and(|color| parse_color(color).is_none()) {
+            return fail("color must be #rgb or #rrggbb");
+        }
+        if style.stroke_width.is_some_and(|width| !(width > 0.0 && width <= MAX_STROKE_WIDTH)) {
+            return fail(&format!("stroke width must be between 0 and {}", MAX_STROKE_WIDTH));
+        }
+        if style.fill_opacity.is_some_and(|opacity| !(0.0..=1.0).contains(&opacity)) {
+            return fail("fill opacity must be between 0 and 1");
+        }
+    }
+
+    Ok(())
+}
+
+// Shapes passed to an overlay or render command; None means none
+pub fn accept(shapes: Option<Vec<Shape>>, version: Option<u32>) -> Result<Vec<Shape>, String> {
+    check_version(version)?;
+    let shapes = shapes.unwrap_or_default();
+    validate(&shapes)?;
+    Ok(shapes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Vec<Shape> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn parses_tagged_shapes_with_style_hints() {
+        let shapes = parse(
+            r##"[
+                {"type": "arrow", "from": {"x": 0.1, "y": 0.1}, "to": {"x": 0.8, "y": 0.9}, "label": "Drag here"},
+                {"type": "polygon", "points": [{"x": 0, "y": 0}, {"x": 1, "y": 0}, {"x": 0.5, "y": 1}],
+                 "style": {"color": "#f80", "fillOpacity": 0.2, "dashed": true}},
+                {"type": "text", "at": {"x": 640, "y": 300, "desktop": true}, "text": "Type your email"},
+                {"type": "badge", "at": {"x": 0.5, "y": 0.5, "screenId": 1}, "number": 2},
+                {"type": "box", "bounds": {"xMin": 0.1, "yMin": 0.1, "xMax": 0.2, "yMax": 0.3}}
+            ]"##,
+        );
+
+        assert!(validate(&shapes).is_ok());
+        assert!(matches!(shapes[0].kind, ShapeKind::Arrow { .. }));
+        assert_eq!(shapes[1].style.color.as_deref().and_then(parse_color), Some([255, 136, 0]));
+        assert!(shapes[2].points()[0].desktop);
+        assert_eq!(shapes[3].points()[0].screen_id, Some(1));
+
+        let json = serde_json::to_value(&shapes[0]).unwrap();
+        assert_eq!(json["type"], "arrow");
+        assert_eq!(json["label"], "Drag here");
+    }
+
+    #[test]
+    fn rejects_malformed_shapes() {
+        let invalid = [
+            r#"[{"type": "polygon", "points": [{"x": 0, "y": 0}, {"x": 1, "y": 1}]}]"#,
+            r#"[{"type": "point", "at": {"x": 1.5, "y": 0.5}}]"#,
+            r#"[{"type": "text", "at": {"x": 0.5, "y": 0.5}, "text": "  "}]"#,
+            r#"[{"type": "badge", "at": {"x": 0.5, "y": 0.5}, "number": 0}]"#,
+            r#"[{"type": "box", "bounds": {"xMin": 0.5, "yMin": 0.5, "xMax": 0.4, "yMax": 0.6}}]"#,
+            r#"[{"type": "point", "at": {"x": 0.5, "y": 0.5}, "style": {"color": "red"}}]"#,
+        ];
+        for json in invalid {
+            assert!(validate(&parse(json)).is_err(), "{}", json);
+        }
+
+        assert!(serde_json::from_str::<Vec<Shape>>(r#"[{"type": "star", "at": {"x": 0, "y": 0}}]"#).is_err());
+        assert!(check_version(Some(PROTOCOL_VERSION)).is_ok());
+        assert!(check_version(Some(PROTOCOL_VERSION + 1)).is_err());
+    }
+}
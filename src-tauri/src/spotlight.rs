@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+
+use crate::capture::Rect;
+use crate::shapes::ShapeKind;
+use crate::OverlayPayload;
+
+// Dims everything except the current step's targets. Sent with a step to turn
+// the mask on for that step; omitted to turn it off.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Spotlight {
+    // Logical pixels of room around each box and shape
+    pub padding: f64,
+    pub corner_radius: f64,
+    // Points get a round hole of this radius
+    pub point_radius: f64,
+    // How dark the mask is, 0..1
+    pub opacity: f64,
+    // Holes in the mask, in logical pixels of the surface it covers. Filled in
+    // by the backend per overlay (or export); anything sent in is ignored.
+    #[serde(skip_deserializing)]
+    pub cutouts: Vec<Cutout>,
+}
+
+impl Default for Spotlight {
+    fn default() -> Self {
+        Self {
+            padding: 12.0,
+            corner_radius: 8.0,
+            point_radius: 36.0,
+            opacity: 0.6,
+            cutouts: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Cutout {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub radius: f64,
+}
+
+impl Cutout {
+    // Rounded-rectangle hit test, for rasterizing the mask
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        if x < self.x || y < self.y || x > self.x + self.width || y > self.y + self.height {
+            return false;
+        }
+        let radius = self.radius.min(self.width / 2.0).min(self.height / 2.0);
+        let cx = x.clamp(self.x + radius, self.x + self.width - radius);
+        let cy = y.clamp(self.y + radius, self.y + self.height - radius);
+        (x - cx).powi(2) + (y - cy).powi(2) <= radius * radius
+    }
+}
+
+fn validate(spotlight: &Spotlight) -> Result<(), String> {
+    let sizes = [spotlight.padding, spotlight.corner_radius, spotlight.point_radius];
+    if !sizes.iter().all(|size| size.is_finite() && *size >= 0.0) {
+        return Err("Spotlight padding and radii must be zero or more".to_string());
+    }
+    if !(0.0..=1.0).contains(&spotlight.opacity) {
+        return Err("Spotlight opacity must be between 0 and 1".to_string());
+    }
+    Ok(())
+}
+
+// Spotlight settings passed to an overlay or render command
+pub fn accept(spotlight: Option<Spotlight>) -> Result<Option<Spotlight>, String> {
+    if let Some(spotlight) = &spotlight {
+        validate(spotlight)?;
+    }
+    Ok(spotlight)
+}
+
+fn around(rect: Rect, padding: f64, radius: f64) -> Cutout {
+    Cutout {
+        x: rect.x - padding,
+        y: rect.y - padding,
+        width: rect.width + 2.0 * padding,
+        height: rect.height + 2.0 * padding,
+        radius,
+    }
+}
+
+fn circle(x: f64, y: f64, radius: f64) -> Cutout {
+    around(Rect { x, y, width: 0.0, height: 0.0 }, radius, radius)
+}
+
+// Holes for every point, box and shape of a payload whose coordinates are
+// normalized to a `width` x `height` logical-pixel surface. Holes entirely off
+// the surface are dropped, so an overlay without targets is dimmed throughout.
+pub fn cutouts(payload: &OverlayPayload, spotlight: &Spotlight, width: f64, height: f64) -> Vec<Cutout> {
+    let pixel = |x: f64, y: f64| (x * width, y * height);
+    let (padding, radius) = (spotlight.padding, spotlight.corner_radius);
+    let mut cutouts = Vec::new();
+
+    for point in &payload.points {
+        let (x, y) = pixel(point.x, point.y);
+        cutouts.push(circle(x, y, spotlight.point_radius));
+    }
+
+    for b in &payload.boxes {
+        let (x0, y0) = pixel(b.x_min, b.y_min);
+        let (x1, y1) = pixel(b.x_max, b.y_max);
+        cutouts.push(around(Rect::enclosing(&[(x0, y0), (x1, y1)]), padding, radius));
+    }
+
+    for shape in &payload.shapes {
+        let points: Vec<(f64, f64)> = shape.points().iter().map(|p| pixel(p.x, p.y)).collect();
+        match &shape.kind {
+            ShapeKind::Point { .. } | ShapeKind::Badge { .. } => {
+                cutouts.push(circle(points[0].0, points[0].1, spotlight.point_radius));
+            }
+            ShapeKind::Box { bounds } => {
+                let corners = [pixel(bounds.x_min, bounds.y_min), pixel(bounds.x_max, bounds.y_max)];
+                cutouts.push(around(Rect::enclosing(&corners), padding, radius));
+            }
+            ShapeKind::Arrow { .. } | ShapeKind::Polyline { .. } | ShapeKind::Polygon { .. } => {
+                cutouts.push(around(Rect::enclosing(&points), padding, radius));
+            }
+            // Text explains the target rather than being one
+            ShapeKind::Text { .. } => {}
+        }
+    }
+
+    cutouts.retain(|c| c.x + c.width > 0.0 && c.y + c.height > 0.0 && c.x < width && c.y < height);
+    cutouts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoundingBox, Point};
+
+    #[test]
+    fn cuts_holes_around_every_target_on_the_surface() {
+        let payload = OverlayPayload {
+            points: vec![Point { x: 0.5, y: 0.5, ..Default::default() }, Point { x: 1.5, y: 0.5, ..Default::default() }],
+            boxes: vec![BoundingBox { x_min: 0.1, y_min: 0.1, x_max: 0.2, y_max: 0.3, ..Default::default() }],
+            shapes: serde_json::from_str(
+                r#"[
+                    {"type": "arrow", "from": {"x": 0.6, "y": 0.8}, "to": {"x": 0.9, "y": 0.6}},
+                    {"type": "text", "at": {"x": 0.1, "y": 0.9}, "text": "Here"}
+                ]"#,
+            )
+            .unwrap(),
+            ..Default::default()
+        };
+        let spotlight = Spotlight { padding: 10.0, corner_radius: 4.0, point_radius: 20.0, ..Default::default() };
+
+        let cutouts = cutouts(&payload, &spotlight, 1000.0, 500.0);
+
+        // The second point is off this surface and the text is not a target
+        assert_eq!(cutouts.len(), 3);
+        assert_eq!(cutouts[0], Cutout { x: 480.0, y: 230.0, width: 40.0, height: 40.0, radius: 20.0 });
+        assert_eq!(cutouts[1], Cutout { x: 90.0, y: 40.0, width: 120.0, height: 120.0, radius: 4.0 });
+        assert_eq!((cutouts[2].x, cutouts[2].y, cutouts[2].width), (590.0, 290.0, 320.0));
+
+        assert!(cutouts[0].contains(500.0, 250.0));
+        assert!(!cutouts[0].contains(482.0, 232.0));
+        assert!(cutouts[1].contains(92.0, 100.0));
+    }
+
+    #[test]
+    fn rejects_out_of_range_settings() {
+        assert!(validate(&Spotlight::default()).is_ok());
+        assert!(validate(&Spotlight { opacity: 1.5, ..Default::default() }).is_err());
+        assert!(validate(&Spotlight { padding: -1.0, ..Default::default() }).is_err());
+    }
+}
@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::shapes::{parse_color, MAX_STROKE_WIDTH};
+
+const THEME_FILE: &str = "overlay-theme.json";
+
+// Built-in looks for the overlay and annotated exports
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Preset {
+    // Red points, green boxes
+    #[default]
+    Standard,
+    // Vermillion and blue from the Okabe-Ito palette, told apart with any
+    // common colour vision deficiency
+    ColorBlindSafe,
+    // Yellow and cyan with dark text and heavier strokes, for busy or dark apps
+    HighContrast,
+    // Thin outlines, no fill and no pulsing
+    Minimal,
+}
+
+// How markers, boxes, default shape colours and labels are drawn. Sent with
+// every overlay payload, already resolved.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlayStyle {
+    // Points, arrows, lines and badges (#rgb or #rrggbb)
+    pub point_color: String,
+    // Boxes and polygons
+    pub box_color: String,
+    // Text on labels and markers
+    pub label_text_color: String,
+    // Logical pixels
+    pub stroke_width: f64,
+    // 0..1, for boxes and polygons
+    pub fill_opacity: f64,
+    // Pulse and ping animations on the current step
+    pub animate: bool,
+}
+
+impl Default for OverlayStyle {
+    fn default() -> Self {
+        Preset::Standard.style()
+    }
+}
+
+impl Preset {
+    pub fn style(self) -> OverlayStyle {
+        let (point_color, box_color, label_text_color, stroke_width, fill_opacity, animate) = match self {
+            Preset::Standard => ("#ef4444", "#22c55e", "#ffffff", 4.0, 0.15, true),
+            Preset::ColorBlindSafe => ("#d55e00", "#0072b2", "#ffffff", 4.0, 0.15, true),
+            Preset::HighContrast => ("#ffff00", "#00ffff", "#000000", 6.0, 0.25, true),
+            Preset::Minimal => ("#ef4444", "#22c55e", "#ffffff", 2.0, 0.0, false),
+        };
+        OverlayStyle {
+            point_color: point_color.to_string(),
+            box_color: box_color.to_string(),
+            label_text_color: label_text_color.to_string(),
+            stroke_width,
+            fill_opacity,
+            animate,
+        }
+    }
+}
+
+// Changes on top of a preset, saved with the theme or sent with one step
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StyleOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub point_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub box_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label_text_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stroke_width: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fill_opacity: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub animate: Option<bool>,
+}
+
+impl StyleOverrides {
+    fn apply(&self, style: &mut OverlayStyle) {
+        let colors = [
+            (&self.point_color, &mut style.point_color),
+            (&self.box_color, &mut style.box_color),
+            (&self.label_text_color, &mut style.label_text_color),
+        ];
+        for (change, color) in colors {
+            if let Some(change) = change {
+                *color = change.clone();
+            }
+        }
+        style.stroke_width = self.stroke_width.unwrap_or(style.stroke_width);
+        style.fill_opacity = self.fill_opacity.unwrap_or(style.fill_opacity);
+        style.animate = self.animate.unwrap_or(style.animate);
+    }
+}
+
+// The overlay look chosen in Settings, persisted in the app data dir
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OverlayTheme {
+    pub preset: Preset,
+    pub overrides: StyleOverrides,
+}
+
+impl OverlayTheme {
+    // Preset, then the saved overrides, then those sent with the step
+    pub fn resolve(&self, step: Option<&StyleOverrides>) -> OverlayStyle {
+        let mut style = self.preset.style();
+        self.overrides.apply(&mut style);
+        if let Some(step) = step {
+            step.apply(&mut style);
+        }
+        style
+    }
+}
+
+pub fn validate(overrides: &StyleOverrides) -> Result<(), String> {
+    let colors = [&overrides.point_color, &overrides.box_color, &overrides.label_text_color];
+    if colors.iter().any(|color| color.as_deref().is_some_and(|color| parse_color(color).is_none())) {
+        return Err("Style colors must be #rgb or #rrggbb".to_string());
+    }
+    if overrides.stroke_width.is_some_and(|width| !(width > 0.0 && width <= MAX_STROKE_WIDTH)) {
+        return Err(format!("Style stroke width must be between 0 and {}", MAX_STROKE_WIDTH));
+    }
+    if overrides.fill_opacity.is_some_and(|opacity| !(0.0..=1.0).contains(&opacity)) {
+        return Err("Style fill opacity must be between 0 and 1".to_string());
+    }
+    Ok(())
+}
+
+// Per-step style passed to an overlay or render command
+pub fn accept(overrides: Option<StyleOverrides>) -> Result<Option<StyleOverrides>, String> {
+    if let Some(overrides) = &overrides {
+        validate(overrides)?;
+    }
+    Ok(overrides)
+}
+
+pub fn load(app: &tauri::AppHandle) -> OverlayTheme {
+    let Ok(dir) = app.path().app_data_dir() else {
+        return OverlayTheme::default();
+    };
+
+    match std::fs::read_to_string(dir.join(THEME_FILE)) {
+        Ok(content) => parse(&content).unwrap_or_else(|e| {
+            println!("[Prism] Ignoring invalid {}: {}", THEME_FILE, e);
+            OverlayTheme::default()
+        }),
+        Err(_) => OverlayTheme::default(),
+    }
+}
+
+// A saved theme, held to the same rules as one set from Settings
+fn parse(content: &str) -> Result<OverlayTheme, String> {
+    let theme: OverlayTheme = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    validate(&theme.overrides)?;
+    Ok(theme)
+}
+
+pub fn save(app: &tauri::AppHandle, theme: &OverlayTheme) -> Result<(), String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let json = serde_json::to_string_pretty(theme).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(THEME_FILE), json).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_step_overrides_over_saved_ones_over_the_preset() {
+        let theme: OverlayTheme = serde_json::from_str(
+            r##"{"preset": "colorBlindSafe", "overrides": {"strokeWidth": 3, "boxColor": "#fff"}}"##,
+        )
+        .unwrap();
+        let step = StyleOverrides { box_color: Some("#000".to_string()), animate: Some(false), ..Default::default() };
+
+        let saved = theme.resolve(None);
+        assert_eq!((saved.point_color.as_str(), saved.box_color.as_str()), ("#d55e00", "#fff"));
+        assert_eq!((saved.stroke_width, saved.animate), (3.0, true));
+
+        let stepped = theme.resolve(Some(&step));
+        assert_eq!((stepped.box_color.as_str(), stepped.stroke_width, stepped.animate), ("#000", 3.0, false));
+
+        // Every preset draws with colours the renderer can parse
+        for preset in [Preset::Standard, Preset::ColorBlindSafe, Preset::HighContrast, Preset::Minimal] {
+            let style = preset.style();
+            assert!([&style.point_color, &style.box_color, &style.label_text_color]
+                .iter()
+                .all(|color| parse_color(color).is_some()));
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_range_overrides() {
+        assert!(validate(&StyleOverrides::default()).is_ok());
+        assert!(validate(&StyleOverrides { point_color: Some("red".to_string()), ..Default::default() }).is_err());
+        assert!(validate(&StyleOverrides { stroke_width: Some(0.0), ..Default::default() }).is_err());
+        assert!(validate(&StyleOverrides { fill_opacity: Some(1.5), ..Default::default() }).is_err());
+
+        // The same rules apply to a theme edited on disk
+        assert!(parse(r#"{"preset": "minimal", "overrides": {"strokeWidth": 3}}"#).is_ok());
+        assert!(parse(r#"{"overrides": {"strokeWidth": 1000}}"#).is_err());
+        assert!(parse(r#"{"overrides": {"pointColor": "url(evil)"}}"#).is_err());
+    }
+}
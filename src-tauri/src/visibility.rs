@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Listener, Manager};
+
+use crate::{overlay, AppState};
+
+pub const VISIBILITY_EVENT: &str = "overlay-visibility";
+// Registered next to CmdOrCtrl+Enter in run()
+pub const PEEK_SHORTCUT: &str = "CmdOrCtrl+Shift+H";
+// Longest auto-hide delay accepted, in seconds
+const MAX_AUTO_HIDE_SECS: f64 = 3600.0;
+
+// Per-step options for getting the overlay out of the way
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VisibilityOptions {
+    // Fade the overlay out this many seconds after the step is shown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_hide_secs: Option<f64>,
+    // Bring a hidden overlay back when the screen watcher sees a change. The
+    // overlay does not sample the screen itself: this only takes effect while
+    // a watcher started with start_screen_watcher is running.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub reappear_on_change: bool,
+}
+
+// Visibility options passed to an overlay command
+pub fn accept(options: Option<VisibilityOptions>) -> Result<Option<VisibilityOptions>, String> {
+    if let Some(secs) = options.as_ref().and_then(|o| o.auto_hide_secs) {
+        if !(secs > 0.0 && secs <= MAX_AUTO_HIDE_SECS) {
+            return Err(format!("autoHideSecs must be above 0 and at most {} seconds", MAX_AUTO_HIDE_SECS));
+        }
+    }
+    Ok(options)
+}
+
+#[derive(Clone, Serialize)]
+struct Visibility {
+    visible: bool,
+}
+
+pub fn send(app: &tauri::AppHandle, screen_id: u32, visible: bool) -> Result<(), String> {
+    app.emit_to(overlay::label(screen_id).as_str(), VISIBILITY_EVENT, Visibility { visible })
+        .map_err(|e| format!("Failed to emit {}: {:?}", VISIBILITY_EVENT, e))
+}
+
+// Fade the overlays in or out without touching the step they show. Showing
+// restarts the step's auto-hide countdown; any change cancels a pending one.
+pub fn set_visible(app: &tauri::AppHandle, visible: bool) {
+    let state = app.state::<AppState>();
+    let (generation, auto_hide) = {
+        let mut current = state.overlay.lock().unwrap();
+        current.hidden = !visible;
+        current.generation += 1;
+        let options = current.payload.as_ref().and_then(|p| p.visibility.as_ref());
+        (current.generation, options.and_then(|o| o.auto_hide_secs))
+    };
+
+    for (screen_id, _) in overlay::windows(app) {
+        let _ = send(app, screen_id, visible);
+    }
+
+    if let (true, Some(secs)) = (visible, auto_hide) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(tokio::time::Duration::from_secs_f64(secs)).await;
+            let current = app.state::<AppState>().overlay.lock().unwrap().generation;
+            if current == generation {
+                set_visible(&app, false);
+            }
+        });
+    }
+}
+
+// The peek shortcut: hide the overlays to read what is under them, press again to bring them back
+pub fn toggle(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let (has_step, hidden) = {
+        let current = state.overlay.lock().unwrap();
+        (current.payload.is_some(), current.hidden)
+    };
+    if has_step && !overlay::windows(app).is_empty() {
+        set_visible(app, hidden);
+    }
+}
+
+// Show hidden overlays again when the screen changes, for steps that ask for it
+pub fn reappear_on_change(app: &tauri::AppHandle) {
+    let handle = app.clone();
+    app.listen("screen-changed", move |_| {
+        let reappear = {
+            let state = handle.state::<AppState>();
+            let current = state.overlay.lock().unwrap();
+            let options = current.payload.as_ref().and_then(|p| p.visibility.as_ref());
+            current.hidden && options.is_some_and(|o| o.reappear_on_change)
+        };
+        if reappear {
+            set_visible(&handle, true);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_only_positive_auto_hide_delays() {
+        let options: VisibilityOptions = serde_json::from_str(r#"{"autoHideSecs": 4}"#).unwrap();
+        assert!(!options.reappear_on_change);
+        assert!(accept(Some(options)).is_ok());
+        assert!(accept(None).is_ok());
+
+        for secs in [0.0, MAX_AUTO_HIDE_SECS + 1.0, f64::MAX, f64::INFINITY, f64::NAN] {
+            let options = VisibilityOptions { auto_hide_secs: Some(secs), ..Default::default() };
+            assert!(accept(Some(options)).is_err(), "{}", secs);
+        }
+    }
+}
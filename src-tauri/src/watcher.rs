@@ -0,0 +1,309 @@
+use screenshots::image::imageops;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::async_runtime::JoinHandle;
+use tauri::{Emitter, Manager};
+
+use crate::capture::{self, Rect, ScreenSelector};
+use crate::change::{self, ChangedRegion, Fingerprint};
+use crate::{exclusion, overlay, AppState};
+
+// Sampling faster than this mostly measures the capture itself
+const MIN_INTERVAL_MS: u64 = 100;
+
+// What the screen watcher samples and how often
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WatchOptions {
+    pub interval_ms: u64,
+    // Logical desktop rectangle to watch; defaults to the area take_screenshot would capture
+    pub region: Option<Rect>,
+    pub screen: Option<ScreenSelector>,
+    // Mean brightness change (0-255) a grid cell needs to count as changed
+    pub threshold: u8,
+    // Sampled frames are downscaled so the longer side is at most this many pixels
+    pub sample_size: u32,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            interval_ms: 750,
+            region: None,
+            screen: None,
+            threshold: change::DEFAULT_THRESHOLD,
+            sample_size: 480,
+        }
+    }
+}
+
+// Payload of the screen-changed event
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenChange {
+    // Fraction of grid cells that changed, 0..1
+    pub score: f64,
+    pub hash_distance: u32,
+    // Bounding box of all changes, normalized 0..1 to `area`
+    pub bounds: Rect,
+    // The same box in logical desktop coordinates
+    pub desktop: Rect,
+    pub regions: Vec<ChangedRegion>,
+    // Logical desktop rectangle that was watched
+    pub area: Rect,
+    pub timestamp: u64,
+}
+
+// Background task that samples the screen and emits screen-changed
+pub struct ScreenWatcher {
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl ScreenWatcher {
+    pub fn new() -> Self {
+        Self { task: Mutex::new(None) }
+    }
+
+    // Start watching, replacing any watcher that is already running
+    pub fn start(&self, app: tauri::AppHandle, options: WatchOptions) {
+        let task = tauri::async_runtime::spawn(run(app, options));
+        if let Some(previous) = self.task.lock().unwrap().replace(task) {
+            previous.abort();
+        }
+    }
+
+    // Returns false when no watcher was running
+    pub fn stop(&self) -> bool {
+        match self.task.lock().unwrap().take() {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+async fn run(app: tauri::AppHandle, options: WatchOptions) {
+    let interval = options.interval_ms.max(MIN_INTERVAL_MS);
+    let mut ticker = tokio::time::interval(tokio::time::Duration::from_millis(interval));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut previous: Option<(Fingerprint, Rect, Option<u64>)> = None;
+
+    println!("[Prism] Screen watcher started ({}ms)", interval);
+
+    loop {
+        ticker.tick().await;
+
+        let mut masks = exclusion::panel_window_rects(&app);
+        let (overlay_rects, overlay_generation) = overlay_masks(&app);
+        masks.extend(overlay_rects);
+
+        // Capturing blocks for tens of milliseconds; keep it off the async runtime
+        let (handle, sample_options) = (app.clone(), options.clone());
+        let sampled = tauri::async_runtime::spawn_blocking(move || {
+            sample(&handle.state::<AppState>(), &sample_options, &masks)
+        })
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|sampled| sampled);
+        let (fingerprint, area) = match sampled {
+            Ok(sampled) => sampled,
+            Err(e) => {
+                println!("[Prism] Screen watcher sample failed: {}", e);
+                continue;
+            }
+        };
+
+        // A moved window or region, or the overlay showing, hiding or moving to
+        // another step, starts a new baseline instead of counting as a change
+        if let Some((before, before_area, before_generation)) = &previous {
+            if *before_area == area && *before_generation == overlay_generation {
+                let report = change::compare(before, &fingerprint, options.threshold, &area);
+                if let Some(change) = ScreenChange::from_report(report, area) {
+                    let _ = app.emit("screen-changed", change);
+                }
+            }
+        }
+
+        previous = Some((fingerprint, area, overlay_generation));
+    }
+}
+
+// Where the step overlay shows up in samples, when content protection can't
+// keep it out: what it draws over the app, masked whether or not it is hidden
+// right now, and the overlay generation it was drawn for
+fn overlay_masks(app: &tauri::AppHandle) -> (Vec<(String, Rect)>, Option<u64>) {
+    if exclusion::OVERLAYS_PROTECTED || overlay::windows(app).is_empty() {
+        return (Vec::new(), None);
+    }
+    let state = app.state::<AppState>();
+    let current = state.overlay.lock().unwrap().clone();
+    let (Some(payload), Some(area)) = (&current.payload, &current.area) else {
+        return (Vec::new(), None);
+    };
+    let rects = match state.backend.screens() {
+        Ok(screens) => overlay::drawn_rects(payload, area, &screens),
+        Err(_) => Vec::new(),
+    };
+    let masks = rects.into_iter().map(|rect| ("overlay".to_string(), rect)).collect();
+    (masks, Some(current.generation))
+}
+
+// Capture one low-resolution frame of the watched area with Prism's windows masked out
+pub fn sample(
+    state: &AppState,
+    options: &WatchOptions,
+    masks: &[(String, Rect)],
+) -> Result<(Fingerprint, Rect), String> {
+    let (screen, area) = match options.region {
+        Some(region) => {
+            let screens = state.backend.screens()?;
+            let screen = capture::resolve_screen(&screens, options.screen, Some(region.center()))?.clone();
+            let area = region
+                .intersect(&screen.bounds())
+                .ok_or_else(|| "Watch region is not on screen".to_string())?;
+            (screen, area)
+        }
+        None => crate::resolve_capture_area(state, options.screen)?,
+    };
+
+    let mut image = state.backend.capture_screen(screen.id)?;
+    for (_, rect) in masks {
+        capture::mask_area(&mut image, rect, &screen);
+    }
+
+    let crop = capture::physical_crop(&area, &screen, image.width(), image.height());
+    let cropped = imageops::crop_imm(&image, crop.x, crop.y, crop.width, crop.height);
+
+    let long_edge = crop.width.max(crop.height).max(1);
+    let scale = (options.sample_size.max(1) as f64 / long_edge as f64).min(1.0);
+    let width = ((crop.width as f64 * scale).round() as u32).max(1);
+    let height = ((crop.height as f64 * scale).round() as u32).max(1);
+    let frame = imageops::thumbnail(&*cropped, width, height);
+
+    Ok((Fingerprint::of(&frame), area))
+}
+
+impl ScreenChange {
+    fn from_report(report: change::ChangeReport, area: Rect) -> Option<Self> {
+        if !report.changed {
+            return None;
+        }
+
+        let union = |rects: Vec<Rect>| {
+            rects.into_iter().reduce(|a, b| {
+                let x = a.x.min(b.x);
+                let y = a.y.min(b.y);
+                Rect {
+                    x,
+                    y,
+                    width: (a.x + a.width).max(b.x + b.width) - x,
+                    height: (a.y + a.height).max(b.y + b.height) - y,
+                }
+            })
+        };
+
+        Some(Self {
+            score: report.score,
+            hash_distance: report.hash_distance,
+            bounds: union(report.regions.iter().map(|r| r.bounds).collect())?,
+            desktop: union(report.regions.iter().map(|r| r.desktop).collect())?,
+            regions: report.regions,
+            area,
+            timestamp: capture::now_millis(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::FakeBackend;
+    use screenshots::image::{Rgba, RgbaImage};
+
+    fn paint(image: &mut RgbaImage, area: (u32, u32, u32, u32), value: u8) {
+        let (x0, y0, width, height) = area;
+        for y in y0..y0 + height {
+            for x in x0..x0 + width {
+                image.put_pixel(x, y, Rgba([value, value, value, 255]));
+            }
+        }
+    }
+
+    #[test]
+    fn detects_changes_in_region_but_not_under_prism_windows() {
+        let dir = std::env::temp_dir().join(format!("prism-watcher-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("screen-0.png");
+        let mut screen = RgbaImage::from_pixel(400, 300, Rgba([255, 255, 255, 255]));
+        screen.save(&path).unwrap();
+
+        let state = AppState::with_backend(Box::new(FakeBackend::from_dir(&dir).unwrap()));
+        let options = WatchOptions {
+            region: Some(Rect { x: 100.0, y: 100.0, width: 200.0, height: 100.0 }),
+            ..WatchOptions::default()
+        };
+        let masks = vec![("main".to_string(), Rect { x: 250.0, y: 100.0, width: 150.0, height: 200.0 })];
+        let (before, area) = sample(&state, &options, &masks).unwrap();
+        assert_eq!(area, options.region.unwrap());
+
+        // Changes under a Prism window and outside the region are ignored
+        paint(&mut screen, (260, 120, 30, 30), 0);
+        paint(&mut screen, (10, 10, 50, 50), 0);
+        screen.save(&path).unwrap();
+        let (after, _) = sample(&state, &options, &masks).unwrap();
+        assert!(!change::compare(&before, &after, options.threshold, &area).changed);
+
+        paint(&mut screen, (150, 150, 20, 20), 0);
+        screen.save(&path).unwrap();
+        let (after, _) = sample(&state, &options, &masks).unwrap();
+        let report = change::compare(&before, &after, options.threshold, &area);
+        let change = ScreenChange::from_report(report, area).unwrap();
+
+        assert!(change.desktop.contains(160.0, 160.0));
+        assert!(change.desktop.x >= 140.0 && change.desktop.x + change.desktop.width <= 180.0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn ignores_overlay_markers_drawn_into_the_frame() {
+        let dir = std::env::temp_dir().join(format!("prism-watcher-overlay-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("screen-0.png");
+        let mut screen = RgbaImage::from_pixel(400, 300, Rgba([255, 255, 255, 255]));
+        screen.save(&path).unwrap();
+
+        let state = AppState::with_backend(Box::new(FakeBackend::from_dir(&dir).unwrap()));
+        let screens = state.backend.screens().unwrap();
+        let area = Rect { x: 0.0, y: 0.0, width: 400.0, height: 300.0 };
+
+        // What Linux samples show under an overlay with a point and a box
+        let payload = crate::OverlayPayload {
+            points: vec![crate::Point { x: 0.25, y: 0.5, ..Default::default() }],
+            boxes: vec![crate::BoundingBox { x_min: 0.5, y_min: 0.25, x_max: 0.9, y_max: 0.75, ..Default::default() }],
+            ..Default::default()
+        };
+        let masks: Vec<(String, Rect)> = overlay::drawn_rects(&payload, &area, &screens)
+            .into_iter()
+            .map(|rect| ("overlay".to_string(), rect))
+            .collect();
+        let options = WatchOptions { region: Some(area), ..WatchOptions::default() };
+        let (before, _) = sample(&state, &options, &masks).unwrap();
+
+        // The marker pulses and the box outline glows: no change
+        paint(&mut screen, (85, 135, 30, 30), 0);
+        paint(&mut screen, (196, 71, 168, 8), 0);
+        screen.save(&path).unwrap();
+        let (after, _) = sample(&state, &options, &masks).unwrap();
+        assert!(!change::compare(&before, &after, options.threshold, &area).changed);
+
+        // The app changes inside the box: reported, so a hidden overlay reappears
+        paint(&mut screen, (260, 120, 40, 40), 0);
+        screen.save(&path).unwrap();
+        let (after, _) = sample(&state, &options, &masks).unwrap();
+        let report = change::compare(&before, &after, options.threshold, &area);
+        assert!(ScreenChange::from_report(report, area).unwrap().desktop.contains(280.0, 140.0));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
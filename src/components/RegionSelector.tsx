@@ -0,0 +1,78 @@
+import { useState, useEffect } from 'react'
+import { getCurrentWindow } from '@tauri-apps/api/window'
+import type { Rect } from '@/types/capture'
+
+interface Drag {
+    startX: number
+    startY: number
+    x: number
+    y: number
+}
+
+// Normalized 0-1 rectangle spanned by a drag, in overlay coordinates
+function dragBounds(drag: Drag): Rect {
+    const { innerWidth: width, innerHeight: height } = window
+    return {
+        x: Math.min(drag.startX, drag.x) / width,
+        y: Math.min(drag.startY, drag.y) / height,
+        width: Math.abs(drag.x - drag.startX) / width,
+        height: Math.abs(drag.y - drag.startY) / height
+    }
+}
+
+// Full-screen crosshair for start_region_selection: drag a rectangle, ESC cancels
+export function RegionSelector() {
+    const [drag, setDrag] = useState<Drag | null>(null)
+
+    const finish = (bounds: Rect | null) => {
+        getCurrentWindow().emit('region-selected', bounds ? { bounds } : null)
+    }
+
+    useEffect(() => {
+        const handleKeyDown = (e: KeyboardEvent) => {
+            if (e.key === 'Escape') finish(null)
+        }
+        window.addEventListener('keydown', handleKeyDown)
+        return () => window.removeEventListener('keydown', handleKeyDown)
+    }, [])
+
+    const bounds = drag ? dragBounds(drag) : null
+
+    return (
+        <div
+            className="fixed inset-0 cursor-crosshair select-none"
+            style={{ background: bounds ? 'transparent' : 'rgba(0, 0, 0, 0.3)' }}
+            onMouseDown={(e) => {
+                if (e.button !== 0) return
+                setDrag({ startX: e.clientX, startY: e.clientY, x: e.clientX, y: e.clientY })
+            }}
+            onMouseMove={(e) => {
+                if (drag) setDrag({ ...drag, x: e.clientX, y: e.clientY })
+            }}
+            onMouseUp={() => {
+                if (!drag) return
+                finish(dragBounds(drag))
+                setDrag(null)
+            }}
+        >
+            {bounds && (
+                <div
+                    className="absolute border-2 border-blue-400"
+                    style={{
+                        left: `${bounds.x * 100}%`,
+                        top: `${bounds.y * 100}%`,
+                        width: `${bounds.width * 100}%`,
+                        height: `${bounds.height * 100}%`,
+                        // Dim everything outside the selection
+                        boxShadow: '0 0 0 9999px rgba(0, 0, 0, 0.3)'
+                    }}
+                />
+            )}
+            {!drag && (
+                <div className="fixed top-8 left-1/2 -translate-x-1/2 bg-black/80 text-white text-sm px-4 py-2 rounded-lg shadow-2xl">
+                    Drag to select a region · ESC to cancel
+                </div>
+            )}
+        </div>
+    )
+}
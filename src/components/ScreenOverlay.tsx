@@ -1,22 +1,116 @@
 import { useState, useEffect } from 'react'
 import { getCurrentWindow } from '@tauri-apps/api/window'
 import { listen } from '@tauri-apps/api/event'
+import { invoke } from '@tauri-apps/api/core'
 import type { Point, BoundingBox } from '@/types/walkthrough'
+import type { CursorMoved, Rect } from '@/types/capture'
+import type { Magnifier, Shape, Spotlight } from '@/types/shapes'
+import type { OverlayData, OverlayLabel, OverlayVisibility } from '@/types/overlay'
+import type { OverlayStyle } from '@/types/theme'
+import { RegionSelector } from './RegionSelector'
 
-interface OverlayData {
-    points: Point[]
-    boxes: BoundingBox[]
-    walkthroughSteps?: number
-    currentStep?: number
-    instruction?: string
-    caption?: string
-    isComplete?: boolean
+// Guide line from the pointer to the nearest target, with the remaining distance.
+// The overlay covers a whole display, so both ends are mapped from desktop coordinates.
+function CursorGuide({ cursor, screen, area }: { cursor: CursorMoved; screen: Rect; area: Rect }) {
+    if (!cursor.target || cursor.distance === null) return null
+    const toScreen = (x: number, y: number) => ({
+        x: (x - screen.x) / screen.width,
+        y: (y - screen.y) / screen.height
+    })
+    const position = toScreen(cursor.desktopX, cursor.desktopY)
+    const target = toScreen(area.x + cursor.target.x * area.width, area.y + cursor.target.y * area.height)
+
+    return (
+        <>
+            <svg className="absolute inset-0 w-full h-full" style={{ zIndex: 8950 }}>
+                <line
+                    x1={`${position.x * 100}%`}
+                    y1={`${position.y * 100}%`}
+                    x2={`${target.x * 100}%`}
+                    y2={`${target.y * 100}%`}
+                    stroke="rgb(96 165 250)"
+                    strokeWidth={2}
+                    strokeDasharray="6 4"
+                    opacity={0.8}
+                />
+            </svg>
+            <div
+                className="absolute bg-blue-500/90 text-white text-xs px-2 py-0.5 rounded shadow-lg whitespace-nowrap"
+                style={{ left: `${position.x * 100}%`, top: `${position.y * 100}%`, transform: 'translate(12px, 12px)', zIndex: 8950 }}
+            >
+                {Math.round(cursor.distance)} px
+            </div>
+        </>
+    )
+}
+
+// Darkens the display except for rounded holes around the step's targets
+function SpotlightMask({ spotlight }: { spotlight: Spotlight }) {
+    return (
+        <svg className="absolute inset-0 w-full h-full transition-opacity duration-500" style={{ zIndex: 8800 }}>
+            <defs>
+                <mask id="spotlight-mask">
+                    <rect width="100%" height="100%" fill="white" />
+                    {spotlight.cutouts.map((cutout, idx) => (
+                        <rect
+                            key={idx}
+                            x={cutout.x}
+                            y={cutout.y}
+                            width={cutout.width}
+                            height={cutout.height}
+                            rx={cutout.radius}
+                            fill="black"
+                        />
+                    ))}
+                </mask>
+            </defs>
+            <rect width="100%" height="100%" fill="black" fillOpacity={spotlight.opacity} mask="url(#spotlight-mask)" />
+        </svg>
+    )
+}
+
+// Zoomed callout of a small target, with a leader from the magnified region.
+// The backend places it so it stays on screen and off the target.
+function MagnifierInset({ magnifier }: { magnifier: Magnifier }) {
+    const { image, source, callout } = magnifier
+    if (!image || !source || !callout) return null
+    const from = { x: source.x + source.width / 2, y: source.y + source.height / 2 }
+    const to = {
+        x: Math.min(Math.max(from.x, callout.x), callout.x + callout.width),
+        y: Math.min(Math.max(from.y, callout.y), callout.y + callout.height)
+    }
+
+    return (
+        <>
+            <svg className="absolute inset-0 w-full h-full" style={{ zIndex: 9050 }}>
+                <rect
+                    x={source.x}
+                    y={source.y}
+                    width={source.width}
+                    height={source.height}
+                    rx={4}
+                    fill="none"
+                    stroke="rgb(168 85 247)"
+                    strokeWidth={2}
+                    strokeDasharray="4 3"
+                />
+                <line x1={from.x} y1={from.y} x2={to.x} y2={to.y} stroke="rgb(168 85 247)" strokeWidth={2} opacity={0.8} />
+            </svg>
+            <img
+                src={image}
+                alt=""
+                className="absolute rounded-lg border-4 border-purple-500 shadow-2xl bg-black"
+                style={{ left: callout.x, top: callout.y, width: callout.width, height: callout.height, zIndex: 9050 }}
+            />
+        </>
+    )
 }
 
 // Reusable component for rendering overlay points
-function OverlayPoint({ point, index, isPrevious, caption }: { point: Point; index: number; isPrevious?: boolean; caption?: string }) {
-    const baseClasses = "absolute rounded-full bg-red-500 border-white shadow-2xl transform -translate-x-1/2 -translate-y-1/2"
-    const sizeClasses = isPrevious ? "w-6 h-6 border-3" : "w-8 h-8 border-4 animate-pulse scale-110"
+function OverlayPoint({ point, index, style, isPrevious }: { point: Point; index: number; style: OverlayStyle; isPrevious?: boolean }) {
+    const animate = style.animate && !isPrevious
+    const baseClasses = "absolute rounded-full border-white shadow-2xl transform -translate-x-1/2 -translate-y-1/2"
+    const sizeClasses = isPrevious ? "w-6 h-6 border-3" : `w-8 h-8 border-4 scale-110 ${animate ? 'animate-pulse' : ''}`
     const opacityClass = isPrevious ? "opacity-30" : "opacity-100 transition-all duration-500"
 
     return (
@@ -26,19 +120,15 @@ function OverlayPoint({ point, index, isPrevious, caption }: { point: Point; ind
             style={{
                 left: `${point.x * 100}%`,
                 top: `${point.y * 100}%`,
+                backgroundColor: style.pointColor,
                 zIndex: isPrevious ? 8900 : 9000,
-                animation: isPrevious ? 'none' : 'pulse 2s cubic-bezier(0.4, 0, 0.6, 1) infinite',
+                animation: animate ? 'pulse 2s cubic-bezier(0.4, 0, 0.6, 1) infinite' : 'none',
             }}
         >
-            {!isPrevious && (
+            {animate && (
                 <>
-                    <div className="absolute inset-0 rounded-full border-2 border-red-400 animate-ping" style={{ animationDuration: '1.5s' }} />
-                    <div className="absolute inset-0 rounded-full border border-red-400 opacity-30" />
-                    {caption && (
-                        <div className="absolute -top-8 left-1/2 transform -translate-x-1/2 bg-red-500 text-white text-sm px-3 py-1 rounded shadow-lg whitespace-nowrap font-medium">
-                            {caption}
-                        </div>
-                    )}
+                    <div className="absolute inset-0 rounded-full border-2 animate-ping" style={{ borderColor: style.pointColor, animationDuration: '1.5s' }} />
+                    <div className="absolute inset-0 rounded-full border opacity-30" style={{ borderColor: style.pointColor }} />
                 </>
             )}
         </div>
@@ -46,9 +136,10 @@ function OverlayPoint({ point, index, isPrevious, caption }: { point: Point; ind
 }
 
 // Reusable component for rendering overlay boxes
-function OverlayBox({ box, index, isPrevious, caption }: { box: BoundingBox; index: number; isPrevious?: boolean; caption?: string }) {
-    const baseClasses = "absolute border-green-500 shadow-2xl"
-    const sizeClasses = isPrevious ? "border-4 bg-green-500/5" : "border-4 bg-green-400/15 scale-105"
+function OverlayBox({ box, index, style, isPrevious }: { box: BoundingBox; index: number; style: OverlayStyle; isPrevious?: boolean }) {
+    const animate = style.animate && !isPrevious
+    const baseClasses = "absolute shadow-2xl"
+    const sizeClasses = isPrevious ? "" : "scale-105"
     const opacityClass = isPrevious ? "opacity-30" : "opacity-100 transition-all duration-500"
 
     return (
@@ -60,32 +151,177 @@ function OverlayBox({ box, index, isPrevious, caption }: { box: BoundingBox; ind
                 top: `${box.yMin * 100}%`,
                 width: `${(box.xMax - box.xMin) * 100}%`,
                 height: `${(box.yMax - box.yMin) * 100}%`,
+                border: `${style.strokeWidth}px solid ${style.boxColor}`,
                 zIndex: isPrevious ? 8900 : 9000,
             }}
         >
-            {!isPrevious && (
-                <>
-                    <div className="absolute inset-0 border-2 border-green-300 animate-pulse" style={{ animationDuration: '1.5s' }} />
-                    {caption && (
-                        <div className="absolute -top-8 left-0 bg-green-500 text-white text-sm px-3 py-1 rounded shadow-lg whitespace-nowrap font-medium">
-                            {caption}
-                        </div>
-                    )}
-                </>
+            <div
+                className="absolute inset-0"
+                style={{ backgroundColor: style.boxColor, opacity: isPrevious ? style.fillOpacity / 3 : style.fillOpacity }}
+            />
+            {animate && (
+                <div className="absolute inset-0 border-2 animate-pulse" style={{ borderColor: style.boxColor, animationDuration: '1.5s' }} />
             )}
         </div>
     )
 }
 
+// Defaults match the legacy markers: the theme's point color for pointing,
+// its box color for areas
+function shapeColor(shape: Shape, style: OverlayStyle) {
+    if (shape.style?.color) return shape.style.color
+    switch (shape.type) {
+        case 'box':
+        case 'polygon':
+            return style.boxColor
+        case 'text':
+            return '#000000'
+        default:
+            return style.pointColor
+    }
+}
+
+// Draws one protocol shape in overlay pixels; the Rust renderer mirrors this for exports
+function OverlayShape({ shape, style }: { shape: Shape; style: OverlayStyle }) {
+    const width = window.innerWidth
+    const height = window.innerHeight
+    const color = shapeColor(shape, style)
+    const strokeWidth = shape.style?.strokeWidth ?? style.strokeWidth
+    const fillOpacity = shape.style?.fillOpacity ?? style.fillOpacity
+    const dash = shape.style?.dashed ? `${strokeWidth * 3} ${strokeWidth * 2}` : undefined
+    const px = (p: Point) => ({ x: p.x * width, y: p.y * height })
+    const path = (points: Point[]) => points.map(p => `${px(p).x},${px(p).y}`).join(' ')
+    const stroke = { stroke: color, strokeWidth, strokeDasharray: dash, strokeLinejoin: 'round' as const }
+
+    let body: JSX.Element
+    switch (shape.type) {
+        case 'point':
+        case 'badge': {
+            const at = px(shape.at)
+            const radius = shape.type === 'point' ? 16 : 14
+            body = (
+                <g>
+                    <circle cx={at.x} cy={at.y} r={radius} fill={color} stroke={shape.type === 'point' ? 'white' : 'none'} strokeWidth={4} />
+                    {shape.type === 'badge' && (
+                        <text x={at.x} y={at.y} fill={style.labelTextColor} fontSize={14} fontWeight={700} textAnchor="middle" dominantBaseline="central">
+                            {shape.number}
+                        </text>
+                    )}
+                </g>
+            )
+            break
+        }
+        case 'box': {
+            const { xMin, yMin, xMax, yMax } = shape.bounds
+            body = (
+                <rect
+                    x={xMin * width}
+                    y={yMin * height}
+                    width={(xMax - xMin) * width}
+                    height={(yMax - yMin) * height}
+                    fill={color}
+                    fillOpacity={fillOpacity}
+                    {...stroke}
+                />
+            )
+            break
+        }
+        case 'arrow': {
+            const from = px(shape.from)
+            const to = px(shape.to)
+            const length = Math.max(Math.hypot(to.x - from.x, to.y - from.y), 1)
+            const [ux, uy] = [(to.x - from.x) / length, (to.y - from.y) / length]
+            const head = Math.min(Math.max(strokeWidth * 4, 12), length)
+            const base = { x: to.x - ux * head, y: to.y - uy * head }
+            body = (
+                <g>
+                    <line x1={from.x} y1={from.y} x2={base.x} y2={base.y} {...stroke} />
+                    <polygon
+                        points={`${to.x},${to.y} ${base.x - uy * head / 2},${base.y + ux * head / 2} ${base.x + uy * head / 2},${base.y - ux * head / 2}`}
+                        fill={color}
+                    />
+                </g>
+            )
+            break
+        }
+        case 'polyline':
+        case 'polygon': {
+            body = shape.type === 'polyline'
+                ? <polyline points={path(shape.points)} fill="none" {...stroke} />
+                : <polygon points={path(shape.points)} fill={color} fillOpacity={fillOpacity} {...stroke} />
+            break
+        }
+        case 'text': {
+            const at = px(shape.at)
+            return (
+                <div
+                    className="absolute text-white text-sm px-3 py-1 rounded shadow-lg whitespace-nowrap font-medium"
+                    style={{ left: at.x, top: at.y, backgroundColor: color, zIndex: 9000 }}
+                >
+                    {shape.text}
+                    {shape.label && <div className="text-xs opacity-80">{shape.label}</div>}
+                </div>
+            )
+        }
+    }
+
+    return (
+        <svg className="absolute inset-0 w-full h-full overflow-visible" style={{ zIndex: 9000 }}>
+            {body}
+        </svg>
+    )
+}
+
+// A caption or shape label where the backend's layout pass put it, with a
+// leader back to the target when it had to move away to stay clear
+function PlacedLabel({ label, color, textColor }: { label: OverlayLabel; color: string; textColor: string }) {
+    return (
+        <>
+            {label.leader && (
+                <svg className="absolute inset-0 w-full h-full" style={{ zIndex: 9000 }}>
+                    <line
+                        x1={label.leader.x1}
+                        y1={label.leader.y1}
+                        x2={label.leader.x2}
+                        y2={label.leader.y2}
+                        stroke={color}
+                        strokeWidth={2}
+                        opacity={0.8}
+                    />
+                </svg>
+            )}
+            <div
+                className="absolute text-sm px-3 py-1 rounded shadow-lg whitespace-nowrap font-medium text-center overflow-hidden text-ellipsis"
+                style={{ left: label.x, top: label.y, width: label.width, height: label.height, backgroundColor: color, color: textColor, zIndex: 9000 }}
+            >
+                {label.text}
+            </div>
+        </>
+    )
+}
+
 export function ScreenOverlay() {
+    // start_region_selection opens this window as overlay.html?mode=select
+    if (new URLSearchParams(window.location.search).get('mode') === 'select') {
+        return <RegionSelector />
+    }
+    return <StepOverlay />
+}
+
+function StepOverlay() {
     const [data, setData] = useState<OverlayData | null>(null)
     const [previousData, setPreviousData] = useState<OverlayData | null>(null)
+    const [cursor, setCursor] = useState<CursorMoved | null>(null)
+    // Hidden by the peek shortcut or an auto-hide timer; the backend decides
+    const [visible, setVisible] = useState(true)
 
     useEffect(() => {
         // Listen for the overlay data
         const setupListener = async () => {
             try {
-                const unlisten = await listen<OverlayData>('overlay-data', (event) => {
+                // Each display has its own overlay; only take data routed to this one
+                const overlayWindow = getCurrentWindow()
+                const unlisten = await overlayWindow.listen<OverlayData>('overlay-data', (event) => {
                     setData(prevData => {
                         // Show previous step briefly for transition effect
                         if (prevData && prevData.currentStep !== event.payload.currentStep) {
@@ -96,11 +332,22 @@ export function ScreenOverlay() {
                     })
                 })
 
+                const unlistenVisibility = await overlayWindow.listen<OverlayVisibility>('overlay-visibility', (event) => {
+                    setVisible(event.payload.visible)
+                })
+
+                const unlistenCursor = await listen<CursorMoved>('cursor-moved', (event) => {
+                    setCursor(event.payload)
+                })
+
                 // Signal that we're ready to receive data
-                const window = getCurrentWindow()
-                await window.emit('overlay-ready', {})
+                await overlayWindow.emit('overlay-ready', { label: overlayWindow.label })
 
-                return unlisten
+                return () => {
+                    unlisten()
+                    unlistenVisibility()
+                    unlistenCursor()
+                }
             } catch (error) {
                 console.error('Error setting up overlay listener:', error)
                 throw error
@@ -112,7 +359,8 @@ export function ScreenOverlay() {
         // Handle ESC key to close overlay
         const handleKeyDown = (e: KeyboardEvent) => {
             if (e.key === 'Escape') {
-                getCurrentWindow().close()
+                // Close the overlays on every display
+                invoke('close_screen_overlay').catch(() => getCurrentWindow().close())
             }
         }
 
@@ -128,32 +376,55 @@ export function ScreenOverlay() {
         return null // Wait for data to arrive
     }
 
+    // Labels take the colour of what they name: points, boxes, the shape's own
+    const labelColor = (label: OverlayLabel) => {
+        const shape = label.target === 'shape' ? data.shapes?.[label.index] : undefined
+        if (shape) return shapeColor(shape, data.style)
+        return label.target === 'box' ? data.style.boxColor : data.style.pointColor
+    }
+
     // Determine if this is a walkthrough
     const isWalkthrough = data.walkthroughSteps && data.walkthroughSteps > 1
 
     return (
-        <div className="fixed inset-0 pointer-events-none">
+        <div className={`fixed inset-0 pointer-events-none transition-opacity duration-500 ${visible ? 'opacity-100' : 'opacity-0 [&_*]:!pointer-events-none'}`}>
+            {data.spotlight && !data.isComplete && <SpotlightMask spotlight={data.spotlight} />}
+
             {/* Render previous step (dimmed ghost) */}
             {previousData && (
                 <>
                     {previousData.points.map((point, idx) => (
-                        <OverlayPoint key={`prev-point-${idx}`} point={point} index={idx} isPrevious />
+                        <OverlayPoint key={`prev-point-${idx}`} point={point} index={idx} style={previousData.style} isPrevious />
                     ))}
                     {previousData.boxes.map((box, idx) => (
-                        <OverlayBox key={`prev-box-${idx}`} box={box} index={idx} isPrevious />
+                        <OverlayBox key={`prev-box-${idx}`} box={box} index={idx} style={previousData.style} isPrevious />
                     ))}
                 </>
             )}
 
             {/* Render current step (highlighted with fade-in) */}
             {data.points.map((point, idx) => (
-                <OverlayPoint key={`point-${idx}`} point={point} index={idx} caption={data.caption} />
+                <OverlayPoint key={`point-${idx}`} point={point} index={idx} style={data.style} />
             ))}
 
             {data.boxes.map((box, idx) => (
-                <OverlayBox key={`box-${idx}`} box={box} index={idx} caption={data.caption} />
+                <OverlayBox key={`box-${idx}`} box={box} index={idx} style={data.style} />
             ))}
 
+            {data.shapes?.map((shape, idx) => (
+                <OverlayShape key={`shape-${idx}`} shape={shape} style={data.style} />
+            ))}
+
+            {data.labels?.map((label, idx) => (
+                <PlacedLabel key={`label-${idx}`} label={label} color={labelColor(label)} textColor={data.style.labelTextColor} />
+            ))}
+
+            {data.magnifier && !data.isComplete && <MagnifierInset magnifier={data.magnifier} />}
+
+            {cursor && !data.isComplete && data.screenBounds && data.area && (
+                <CursorGuide cursor={cursor} screen={data.screenBounds} area={data.area} />
+            )}
+
             {/* Step counter and instruction for walkthroughs */}
             {isWalkthrough && (
                 <div className={`fixed top-8 left-8 bg-black/80 backdrop-blur text-white px-6 py-4 rounded-lg shadow-2xl pointer-events-auto border-2 max-w-md transition-all duration-500 ${
@@ -180,13 +451,13 @@ export function ScreenOverlay() {
                         {!data.isComplete && (
                             <div>Click "Proceed" in the chat to continue (or press <kbd>⌘+Enter</kbd>)</div>
                         )}
-                        <div>ESC to close</div>
+                        <div><kbd>⌘+Shift+H</kbd> to peek underneath, ESC to close</div>
                     </div>
                 </div>
             )}
 
             {/* ESC hint (non-walkthrough) */}
-            {!isWalkthrough && (
+            {!isWalkthrough && data.isHome !== false && (
                 <div className="fixed bottom-8 left-8 bg-black/70 backdrop-blur text-white px-4 py-2 rounded text-xs text-gray-400 pointer-events-auto border border-gray-600/50">
                     Press ESC to close
                 </div>
@@ -2,13 +2,7 @@ import { useState, useEffect } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import { X, Monitor } from 'lucide-react'
 import { Button } from './ui/button'
-
-interface FocusedWindowInfo {
-    owner_name: string
-    window_name: string
-    window_id: number
-    process_id: number
-}
+import type { FocusedWindowInfo } from '@/types/capture'
 
 export function SelectionBanner() {
     const [windows, setWindows] = useState<FocusedWindowInfo[]>([])
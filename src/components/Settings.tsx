@@ -2,9 +2,209 @@ import { useEffect, useRef, useState } from 'react'
 import { Button } from '@/components/ui/button'
 import { Card } from '@/components/ui/card'
 import { getCurrentWindow } from '@tauri-apps/api/window'
+import { getRedactionRules, setRedactionRules } from '@/services/redaction'
+import { getRetentionPolicy, setRetentionPolicy } from '@/services/history'
+import { getOverlayTheme, setOverlayTheme } from '@/services/theme'
+import type { RedactionRule } from '@/types/redaction'
+import type { OverlayTheme, StylePreset } from '@/types/theme'
 
 const STORAGE_KEY = 'prism_gemini_api_key'
 
+function describeRule(rule: RedactionRule): string {
+  if (rule.label) return rule.label
+  switch (rule.target.kind) {
+    case 'app':
+      return `${rule.target.appName} windows`
+    case 'rect': {
+      const { x, y, width, height } = rule.target.rect
+      return `Screen area ${Math.round(width)}×${Math.round(height)} at ${Math.round(x)}, ${Math.round(y)}`
+    }
+    case 'region':
+      return 'Drawn region'
+  }
+}
+
+// Redaction rules are applied on the Rust side before screenshots are encoded
+function RedactionSettings() {
+  const [rules, setRules] = useState<RedactionRule[]>([])
+  const [appName, setAppName] = useState('')
+  const [error, setError] = useState('')
+
+  useEffect(() => {
+    getRedactionRules().then(setRules).catch((e) => setError(String(e)))
+  }, [])
+
+  const save = async (next: RedactionRule[]) => {
+    try {
+      await setRedactionRules(next)
+      setRules(next)
+      setError('')
+    } catch (e) {
+      setError(String(e))
+    }
+  }
+
+  const addAppRule = () => {
+    const name = appName.trim()
+    if (!name) return
+    save([...rules, {
+      id: `app-${Date.now()}`,
+      enabled: true,
+      style: 'blackout',
+      target: { kind: 'app', appName: name }
+    }])
+    setAppName('')
+  }
+
+  const updateRule = (id: string, changes: Partial<RedactionRule>) =>
+    save(rules.map(rule => rule.id === id ? { ...rule, ...changes } : rule))
+
+  return (
+    <div className="space-y-2 mb-6">
+      <label className="text-sm text-zinc-300">Redaction</label>
+      <p className="text-xs text-zinc-500">Matching areas are blacked out or blurred before screenshots leave your machine.</p>
+      {rules.map(rule => (
+        <div key={rule.id} className="flex items-center gap-2 text-sm text-zinc-300">
+          <input
+            type="checkbox"
+            checked={rule.enabled}
+            onChange={(e) => updateRule(rule.id, { enabled: e.target.checked })}
+          />
+          <span className="flex-1 truncate">{describeRule(rule)}</span>
+          <select
+            value={rule.style}
+            onChange={(e) => updateRule(rule.id, { style: e.target.value as RedactionRule['style'] })}
+            className="bg-zinc-800/80 border border-zinc-700/50 rounded-lg px-2 py-1 text-xs"
+          >
+            <option value="blackout">Black out</option>
+            <option value="blur">Blur</option>
+          </select>
+          <Button
+            variant="ghost"
+            size="icon"
+            className="h-6 w-6 hover:bg-red-500/20"
+            onClick={() => save(rules.filter(r => r.id !== rule.id))}
+          >
+            <span className="text-white">✕</span>
+          </Button>
+        </div>
+      ))}
+      <div className="flex items-center gap-2">
+        <input
+          value={appName}
+          onChange={(e) => setAppName(e.target.value)}
+          onKeyDown={(e) => { if (e.key === 'Enter') addAppRule() }}
+          placeholder="App name, e.g. 1Password"
+          className="flex-1 bg-zinc-800/80 text-white placeholder:text-zinc-500 border border-zinc-700/50 rounded-xl px-4 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500/50 focus:border-blue-500/50"
+        />
+        <Button variant="ghost" onClick={addAppRule} className="hover:bg-white/10">Add app</Button>
+      </div>
+      {error && <p className="text-xs text-red-400">{error}</p>}
+    </div>
+  )
+}
+
+const PRESETS: { value: StylePreset; label: string }[] = [
+  { value: 'standard', label: 'Standard (red and green)' },
+  { value: 'colorBlindSafe', label: 'Colour-blind safe (orange and blue)' },
+  { value: 'highContrast', label: 'High contrast (yellow and cyan)' },
+  { value: 'minimal', label: 'Minimal (thin outlines, no animation)' }
+]
+
+// Overlay look, saved by the backend and applied to every step and export
+function OverlayThemeSettings() {
+  const [theme, setTheme] = useState<OverlayTheme | null>(null)
+  const [error, setError] = useState('')
+
+  useEffect(() => {
+    getOverlayTheme().then(setTheme).catch((e) => setError(String(e)))
+  }, [])
+
+  const save = async (next: OverlayTheme) => {
+    try {
+      await setOverlayTheme(next)
+      setTheme(next)
+      setError('')
+    } catch (e) {
+      setError(String(e))
+    }
+  }
+
+  if (!theme) return null
+
+  return (
+    <div className="space-y-2 mb-6">
+      <label className="text-sm text-zinc-300">Overlay style</label>
+      <p className="text-xs text-zinc-500">Colours and weights for markers, boxes and labels. Applies from the next step shown.</p>
+      <select
+        value={theme.preset}
+        onChange={(e) => save({ ...theme, preset: e.target.value as StylePreset })}
+        className="w-full bg-zinc-800/80 text-white border border-zinc-700/50 rounded-lg px-2 py-1.5 text-sm"
+      >
+        {PRESETS.map(preset => (
+          <option key={preset.value} value={preset.value}>{preset.label}</option>
+        ))}
+      </select>
+      {Object.keys(theme.overrides).length > 0 && (
+        <div className="flex items-center gap-2 text-xs text-zinc-500">
+          <span className="flex-1">Custom changes on top of the preset are in use.</span>
+          <Button variant="ghost" onClick={() => save({ ...theme, overrides: {} })} className="hover:bg-white/10">Reset</Button>
+        </div>
+      )}
+      {error && <p className="text-xs text-red-400">{error}</p>}
+    </div>
+  )
+}
+
+const MB = 1024 * 1024
+
+// Captures are kept on disk and swept by the backend according to this policy
+function RetentionSettings() {
+  const [days, setDays] = useState('')
+  const [megabytes, setMegabytes] = useState('')
+  const [status, setStatus] = useState('')
+
+  useEffect(() => {
+    getRetentionPolicy()
+      .then(policy => {
+        setDays(policy.maxAgeHours === null ? '' : String(policy.maxAgeHours / 24))
+        setMegabytes(policy.maxTotalBytes === null ? '' : String(Math.round(policy.maxTotalBytes / MB)))
+      })
+      .catch((e) => setStatus(String(e)))
+  }, [])
+
+  const handleSave = async () => {
+    // Empty fields mean no limit
+    const parse = (value: string, scale: number) => {
+      const n = parseFloat(value)
+      return value.trim() === '' || !(n >= 0) ? null : Math.round(n * scale)
+    }
+    try {
+      await setRetentionPolicy({ maxAgeHours: parse(days, 24), maxTotalBytes: parse(megabytes, MB) })
+      setStatus('Saved')
+    } catch (e) {
+      setStatus(String(e))
+    }
+  }
+
+  const inputClass = "w-20 bg-zinc-800/80 text-white border border-zinc-700/50 rounded-lg px-2 py-1 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500/50"
+
+  return (
+    <div className="space-y-2 mb-6">
+      <label className="text-sm text-zinc-300">Capture history</label>
+      <div className="flex items-center gap-2 text-sm text-zinc-400">
+        <span>Keep for</span>
+        <input value={days} onChange={(e) => setDays(e.target.value)} placeholder="∞" className={inputClass} />
+        <span>days, up to</span>
+        <input value={megabytes} onChange={(e) => setMegabytes(e.target.value)} placeholder="∞" className={inputClass} />
+        <span>MB</span>
+        <Button variant="ghost" onClick={handleSave} className="ml-auto hover:bg-white/10">Apply</Button>
+      </div>
+      {status && <p className="text-xs text-zinc-500">{status}</p>}
+    </div>
+  )
+}
+
 export function Settings() {
   const [apiKey, setApiKey] = useState('')
   const [saved, setSaved] = useState(false)
@@ -64,7 +264,7 @@ export function Settings() {
             </Button>
           </div>
         </div>
-        <div className="p-6">
+        <div className="p-6 max-h-[calc(100vh-6rem)] overflow-y-auto">
           <div className="mb-4">
             <p className="text-sm text-zinc-400">Configure API keys used by Prism.</p>
           </div>
@@ -126,6 +326,12 @@ export function Settings() {
             <p className="text-xs text-zinc-500">Stored locally and used at runtime. You only need to set this once.</p>
           </div>
 
+          <RedactionSettings />
+
+          <OverlayThemeSettings />
+
+          <RetentionSettings />
+
           <div className="flex items-center gap-2 justify-end">
             {saved && <span className="text-xs text-green-400 mr-auto">Saved</span>}
             <Button variant="ghost" onClick={handleClose} className="hover:bg-white/10">Close</Button>
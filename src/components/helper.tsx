@@ -1,11 +1,33 @@
 import { useState, useRef, useEffect } from 'react'
 import { ScrollArea } from '@/components/ui/scroll-area'
 import { Button } from '@/components/ui/button'
-import { Send } from 'lucide-react'
+import { Send, Crop, ClipboardPaste, Focus } from 'lucide-react'
 import { invoke } from '@tauri-apps/api/core'
+import { getCurrentWebview } from '@tauri-apps/api/webview'
 import { listen } from '@tauri-apps/api/event'
 import { geminiService } from '@/services/gemini'
+import { captureToDataUrl, compareCaptures, selectRegion, loadImageFile, loadClipboardImage, copyAnnotatedCapture } from '@/services/captures'
+import { startScreenWatcher, stopScreenWatcher, targetRegion } from '@/services/watcher'
+import { startCursorStream, stopCursorStream, cursorTargets, startClickListener, stopClickListener } from '@/services/cursor'
 import type { Point, BoundingBox, Message, WalkthroughStep, WalkthroughSession } from '@/types/walkthrough'
+import type { CaptureResult, EncodeOptions, Rect, ScreenChange, TargetClick } from '@/types/capture'
+import type { VisibilityOptions } from '@/types/overlay'
+import type { MagnifierOptions } from '@/types/shapes'
+import type { StyleOverrides } from '@/types/theme'
+
+// Keep screenshots sent to the model small: JPEG, capped resolution and size
+const CAPTURE_ENCODING: EncodeOptions = {
+    format: 'jpeg',
+    maxLongEdge: 1600,
+    quality: 80,
+    maxBytes: 1_000_000
+}
+
+// An overlay peeked away with Cmd/Ctrl+Shift+H comes back once the screen changes
+const OVERLAY_VISIBILITY: VisibilityOptions = { reappearOnChange: true }
+
+// Targets too small to make out get a zoomed inset; larger ones are left alone by the backend
+const OVERLAY_MAGNIFIER: MagnifierOptions = {}
 
 export function Helper() {
     const [messages, setMessages] = useState<Message[]>([])
@@ -14,6 +36,17 @@ export function Helper() {
     const [statusMessage, setStatusMessage] = useState<string>('')
     const [walkthroughSession, setWalkthroughSession] = useState<WalkthroughSession | null>(null)
     const overlayWindowExistsRef = useRef<boolean>(false)
+    const lastCaptureRef = useRef<CaptureResult | null>(null)
+    // Desktop rectangle the user dragged out; screenshots are limited to it until cleared
+    const [region, setRegion] = useState<Rect | null>(null)
+    // Image dropped on the panel or pasted from the clipboard; used instead of the screen until cleared
+    const [imported, setImported] = useState<CaptureResult | null>(null)
+    const importedRef = useRef<CaptureResult | null>(null)
+    // Dim everything but the step's targets; read through the ref by overlay updates
+    const [spotlight, setSpotlight] = useState(false)
+    const spotlightRef = useRef(false)
+    // Groups this conversation's captures in the on-disk history
+    const sessionIdRef = useRef<string>(`session-${Date.now()}`)
     const scrollRef = useRef<HTMLDivElement>(null)
     const inputRef = useRef<HTMLInputElement>(null)
     const proceedHandlerRef = useRef<(() => Promise<void>) | null>(null)
@@ -72,6 +105,105 @@ export function Helper() {
         }
     }, [])
 
+    // Auto-advance when the user clicks the highlighted target
+    useEffect(() => {
+        let unlisten: (() => void) | undefined
+
+        const setupListener = async () => {
+            unlisten = await listen<TargetClick>('target-clicked', async (event) => {
+                if (isExecutingShortcut.current || !proceedHandlerRef.current) return
+
+                console.log('[Click Listener] Target clicked:', event.payload.target)
+                isExecutingShortcut.current = true
+                try {
+                    await proceedHandlerRef.current()
+                } catch (err) {
+                    console.error('[Click Listener] Error executing proceed handler:', err)
+                } finally {
+                    isExecutingShortcut.current = false
+                }
+            })
+        }
+
+        setupListener()
+
+        return () => {
+            if (unlisten) unlisten()
+        }
+    }, [])
+
+    // Auto-advance when the highlighted target area changes on screen
+    useEffect(() => {
+        let unlisten: (() => void) | undefined
+
+        const setupListener = async () => {
+            unlisten = await listen<ScreenChange>('screen-changed', async (event) => {
+                if (isExecutingShortcut.current || !proceedHandlerRef.current) return
+
+                console.log('[Screen Watcher] Target area changed:', event.payload.score)
+                isExecutingShortcut.current = true
+                try {
+                    await proceedHandlerRef.current()
+                } catch (err) {
+                    console.error('[Screen Watcher] Error executing proceed handler:', err)
+                } finally {
+                    isExecutingShortcut.current = false
+                }
+            })
+        }
+
+        setupListener()
+
+        return () => {
+            if (unlisten) unlisten()
+        }
+    }, [])
+
+    // Watch the current step's targets so the walkthrough advances once the user acts
+    const watchStepTargets = async (step: WalkthroughStep, isComplete: boolean) => {
+        const capture = lastCaptureRef.current
+        // Nothing on screen to watch for imported images
+        if (capture && (capture.source === 'file' || capture.source === 'clipboard')) return
+        const region = capture ? targetRegion(capture, step.points, step.boxes) : null
+
+        try {
+            if (isComplete || !region) {
+                await stopScreenWatcher()
+            } else {
+                await startScreenWatcher({ region })
+            }
+        } catch (error) {
+            console.error('Failed to update screen watcher:', error)
+        }
+
+        // The overlay draws a guide line from the pointer to the nearest target;
+        // the stream reads the targets of whatever step the overlay shows
+        const targets = cursorTargets(step.points, step.boxes)
+        try {
+            if (isComplete || !capture || targets.length === 0) {
+                await stopCursorStream()
+            } else {
+                await startCursorStream({ captureId: capture.captureId })
+            }
+        } catch (error) {
+            console.error('Failed to update cursor stream:', error)
+        }
+
+        // Clicking the highlighted target advances without Cmd+Enter
+        try {
+            if (isComplete || !capture || targets.length === 0) {
+                await stopClickListener()
+            } else {
+                await startClickListener()
+            }
+        } catch (error) {
+            console.error('Failed to update click listener:', error)
+        }
+    }
+
+    // Empty options take the backend's default padding and darkness
+    const spotlightOptions = () => (spotlightRef.current ? {} : undefined)
+
     const openScreenOverlay = async (
         points: Point[] = [],
         boxes: BoundingBox[] = [],
@@ -79,17 +211,39 @@ export function Helper() {
         currentStep?: number,
         instruction?: string,
         caption?: string,
-        isComplete?: boolean
+        isComplete?: boolean,
+        style?: StyleOverrides
     ) => {
+        // Imported images aren't on screen; hand back an annotated copy instead
+        const capture = lastCaptureRef.current
+        if (capture && (capture.source === 'file' || capture.source === 'clipboard')) {
+            try {
+                await copyAnnotatedCapture(capture.captureId, {
+                    points, boxes, walkthroughSteps, currentStep, instruction, caption, isComplete,
+                    spotlight: spotlightOptions(),
+                    style
+                })
+                setMessages(prev => [...prev, createAssistantMessage('Annotated image copied to the clipboard.')])
+            } catch (error) {
+                console.error('Failed to copy annotated image:', error)
+            }
+            return
+        }
+
         try {
             await invoke('open_screen_overlay', {
+                captureId: lastCaptureRef.current?.captureId,
                 points,
                 boxes,
-                walkthrough_steps: walkthroughSteps,
-                current_step: currentStep,
+                walkthroughSteps,
+                currentStep,
                 instruction,
                 caption,
-                is_complete: isComplete
+                isComplete,
+                spotlight: spotlightOptions(),
+                visibility: OVERLAY_VISIBILITY,
+                magnifier: OVERLAY_MAGNIFIER,
+                style
             })
         } catch (error) {
             console.error('Failed to open screen overlay:', error)
@@ -113,22 +267,35 @@ export function Helper() {
 
         try {
             setIsProcessing(true)
-            setStatusMessage('Preparing for next step...')
-
-            // Close the overlay before taking screenshot
-            await invoke('close_screen_overlay')
-            overlayWindowExistsRef.current = false
-
-            // Small delay to ensure overlay is fully closed
-            await new Promise(resolve => setTimeout(resolve, 100))
-
+            // take_screenshot hides Prism's windows (including the overlay) while capturing
             setStatusMessage('Taking screenshot for next step...')
-            const screenshotDataUrl = await invoke<string>('take_screenshot')
+            const previousCaptureId = lastCaptureRef.current?.captureId
+            const capture = await invoke<CaptureResult>('take_screenshot', {
+                region,
+                encoding: CAPTURE_ENCODING,
+                sessionId: sessionIdRef.current
+            })
+            lastCaptureRef.current = capture
+
+            // Don't ask the model again if the user hasn't done anything yet
+            if (previousCaptureId) {
+                const change = await compareCaptures(previousCaptureId, capture.captureId).catch(() => null)
+                if (change && !change.changed) {
+                    console.log('[Proceed Handler] Screen unchanged, skipping model call')
+                    setMessages(prev => [...prev, {
+                        id: Date.now().toString(),
+                        role: 'assistant',
+                        content: 'Nothing on screen has changed yet. Complete the current step, then press Cmd+Enter.'
+                    }])
+                    return
+                }
+            }
+
             setStatusMessage('Determining next step...')
 
             const previousSteps = session.steps.map(s => s.instruction)
             const stepResult = await geminiService.walkthroughNextStep(
-                screenshotDataUrl,
+                await captureToDataUrl(capture),
                 session.goal,
                 previousSteps
             )
@@ -143,7 +310,7 @@ export function Helper() {
 
             const newStep: WalkthroughStep = {
                 stepNumber: session.steps.length + 1,
-                screenshot: screenshotDataUrl,
+                screenshot: capture.url,
                 caption: stepResult.caption,
                 instruction: stepResult.instruction,
                 points: stepResult.points,
@@ -159,12 +326,13 @@ export function Helper() {
 
             setWalkthroughSession(updatedSession)
             await updateOverlayWithSession(updatedSession)
+            await watchStepTargets(newStep, updatedSession.isComplete)
 
             const assistantMessage: Message = {
                 id: Date.now().toString(),
                 role: 'assistant',
                 content: `Step ${newStep.stepNumber}: ${newStep.instruction}`,
-                image: screenshotDataUrl,
+                image: capture.url,
                 points: newStep.points,
                 boxes: newStep.boxes,
                 caption: newStep.caption
@@ -185,7 +353,7 @@ export function Helper() {
     useEffect(() => {
         proceedHandlerRef.current = handleProceedToNextStep
         console.log('[Global Shortcut] Handler ref updated - processing:', isProcessing, 'session:', !!walkthroughSession)
-    }, [walkthroughSession, isProcessing])
+    }, [walkthroughSession, isProcessing, region])
 
     const updateOverlayWithSession = async (session: WalkthroughSession) => {
         const currentStep = session.steps[session.currentStepIndex]
@@ -195,13 +363,18 @@ export function Helper() {
             if (overlayWindowExistsRef.current) {
                 // Update existing overlay window
                 await invoke('update_screen_overlay_data', {
+                    captureId: lastCaptureRef.current?.captureId,
                     points: currentStep.points,
                     boxes: currentStep.boxes,
-                    walkthrough_steps: session.steps.length,
-                    current_step: session.currentStepIndex + 1,
+                    walkthroughSteps: session.steps.length,
+                    currentStep: session.currentStepIndex + 1,
                     instruction: currentStep.instruction,
                     caption: currentStep.caption,
-                    is_complete: session.isComplete
+                    isComplete: session.isComplete,
+                    spotlight: spotlightOptions(),
+                    visibility: OVERLAY_VISIBILITY,
+                    magnifier: OVERLAY_MAGNIFIER,
+                    style: currentStep.style
                 })
             } else {
                 // Create new overlay window for first step
@@ -212,7 +385,8 @@ export function Helper() {
                     session.currentStepIndex + 1,
                     currentStep.instruction,
                     currentStep.caption,
-                    session.isComplete
+                    session.isComplete,
+                    currentStep.style
                 )
                 overlayWindowExistsRef.current = true
             }
@@ -239,10 +413,80 @@ export function Helper() {
         caption
     })
 
+    const selectImported = (capture: CaptureResult | null) => {
+        importedRef.current = capture
+        setImported(capture)
+        if (capture) setRegion(null)
+    }
+
+    // Drop an image file on the panel to analyze it instead of the screen
+    useEffect(() => {
+        const unlistenPromise = getCurrentWebview().onDragDropEvent(async (event) => {
+            if (event.payload.type !== 'drop' || event.payload.paths.length === 0) return
+            try {
+                selectImported(await loadImageFile(event.payload.paths[0], CAPTURE_ENCODING, sessionIdRef.current))
+            } catch (error) {
+                console.error('Failed to load dropped image:', error)
+                setStatusMessage(`Error: ${error}`)
+            }
+        })
+        return () => {
+            unlistenPromise.then(fn => fn())
+        }
+    }, [])
+
+    const handlePasteImage = async () => {
+        try {
+            selectImported(await loadClipboardImage(CAPTURE_ENCODING, sessionIdRef.current))
+        } catch (error) {
+            console.error('Failed to load clipboard image:', error)
+            setStatusMessage(`Error: ${error}`)
+        }
+    }
+
     // Helper to take screenshot with fade animation
-    const takeScreenshot = async (): Promise<string> => {
+    const takeScreenshot = async (): Promise<CaptureResult> => {
         setStatusMessage('Analyzing...')
-        return await invoke<string>('take_screenshot')
+        if (importedRef.current) {
+            lastCaptureRef.current = importedRef.current
+            return importedRef.current
+        }
+        const capture = await invoke<CaptureResult>('take_screenshot', {
+            region,
+            encoding: CAPTURE_ENCODING,
+            sessionId: sessionIdRef.current
+        })
+        lastCaptureRef.current = capture
+        if (capture.windowCaptureError) {
+            console.warn('[Capture] Fell back to screen pixels:', capture.windowCaptureError)
+        }
+        return capture
+    }
+
+    // Takes effect on the next overlay update; a running walkthrough is re-sent right away
+    const toggleSpotlight = () => {
+        spotlightRef.current = !spotlightRef.current
+        setSpotlight(spotlightRef.current)
+        if (walkthroughSession && overlayWindowExistsRef.current) {
+            updateOverlayWithSession(walkthroughSession)
+        }
+    }
+
+    const handleSelectRegion = async () => {
+        if (isProcessing) return
+        try {
+            const selected = await selectRegion(CAPTURE_ENCODING, sessionIdRef.current)
+            if (selected) {
+                // The overlay, if any, was replaced by the selection window
+                overlayWindowExistsRef.current = false
+                lastCaptureRef.current = selected.capture
+                selectImported(null)
+                setRegion(selected.rect)
+            }
+        } catch (error) {
+            console.error('Region selection failed:', error)
+            setStatusMessage(`Error: ${error}`)
+        }
     }
 
     // Intent Handlers
@@ -253,13 +497,13 @@ export function Helper() {
     }
 
     const handlePointIntent = async (query: string) => {
-        const screenshot = await takeScreenshot()
+        const capture = await takeScreenshot()
         setStatusMessage(`Finding "${query}"...`)
-        const result = await geminiService.point(screenshot, query)
+        const result = await geminiService.point(await captureToDataUrl(capture), query)
 
         setMessages(prev => [...prev, createAssistantMessage(
             `Found ${result.points.length} instance(s) of "${query}"`,
-            screenshot,
+            capture.url,
             result.points
         )])
 
@@ -267,13 +511,13 @@ export function Helper() {
     }
 
     const handleDetectIntent = async (query: string) => {
-        const screenshot = await takeScreenshot()
+        const capture = await takeScreenshot()
         setStatusMessage(`Detecting "${query}"...`)
-        const result = await geminiService.detect(screenshot, query)
+        const result = await geminiService.detect(await captureToDataUrl(capture), query)
 
         setMessages(prev => [...prev, createAssistantMessage(
             `Detected ${result.objects.length} object(s) matching "${query}"`,
-            screenshot,
+            capture.url,
             undefined,
             result.objects
         )])
@@ -282,22 +526,22 @@ export function Helper() {
     }
 
     const handleQueryIntent = async (query: string) => {
-        const screenshot = await takeScreenshot()
+        const capture = await takeScreenshot()
         setStatusMessage('Answering...')
-        const result = await geminiService.query(screenshot, query)
+        const result = await geminiService.query(await captureToDataUrl(capture), query)
 
-        setMessages(prev => [...prev, createAssistantMessage(result.answer, screenshot)])
+        setMessages(prev => [...prev, createAssistantMessage(result.answer, capture.url)])
     }
 
     const handleWalkthroughIntent = async (query: string) => {
-        const screenshot = await takeScreenshot()
+        const capture = await takeScreenshot()
         setStatusMessage(`Starting walkthrough for "${query}"...`)
 
-        const stepResult = await geminiService.walkthroughNextStep(screenshot, query, [])
+        const stepResult = await geminiService.walkthroughNextStep(await captureToDataUrl(capture), query, [])
 
         const firstStep: WalkthroughStep = {
             stepNumber: 1,
-            screenshot,
+            screenshot: capture.url,
             caption: stepResult.caption,
             instruction: stepResult.instruction,
             points: stepResult.points,
@@ -315,7 +559,7 @@ export function Helper() {
         setWalkthroughSession(newSession)
         setMessages(prev => [...prev, createAssistantMessage(
             `Step 1: ${firstStep.instruction}`,
-            screenshot,
+            capture.url,
             firstStep.points,
             firstStep.boxes,
             firstStep.caption
@@ -331,12 +575,17 @@ export function Helper() {
             stepResult.isComplete
         )
         overlayWindowExistsRef.current = true
+        await watchStepTargets(firstStep, stepResult.isComplete)
     }
 
     const handleSend = async () => {
         if (!input.trim() || isProcessing) return
 
         setMessages([]) // Clear previous conversation - helper is stateless
+        sessionIdRef.current = `session-${Date.now()}`
+        await stopScreenWatcher().catch(() => false)
+        await stopCursorStream().catch(() => false)
+        await stopClickListener().catch(() => false)
         setIsProcessing(true)
 
         const userMessage = createAssistantMessage(input)
@@ -427,6 +676,18 @@ export function Helper() {
             </div>
 
             <div className="p-4 border-t border-zinc-800/50 bg-zinc-900/50 backdrop-blur space-y-3">
+                {imported && (
+                    <div className="flex items-center gap-2 text-xs text-zinc-400">
+                        <span>Using {imported.source === 'file' ? 'a dropped image' : 'the clipboard image'} ({imported.crop.width}×{imported.crop.height}) instead of the screen</span>
+                        <button onClick={() => selectImported(null)} className="text-zinc-500 hover:text-white">✕</button>
+                    </div>
+                )}
+                {region && (
+                    <div className="flex items-center gap-2 text-xs text-zinc-400">
+                        <span>Limited to a {Math.round(region.width)}×{Math.round(region.height)} region</span>
+                        <button onClick={() => setRegion(null)} className="text-zinc-500 hover:text-white">✕</button>
+                    </div>
+                )}
                 {statusMessage && (
                     <div className="text-xs text-blue-400">
                         {statusMessage}
@@ -485,6 +746,32 @@ export function Helper() {
                         placeholder="Ask for help"
                         className="flex-1 bg-zinc-800/80 text-white placeholder:text-zinc-500 border border-zinc-700/50 rounded-xl px-4 py-2.5 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500/50 focus:border-blue-500/50 transition-all"
                     />
+                    <Button
+                        variant="ghost"
+                        onClick={handlePasteImage}
+                        disabled={isProcessing}
+                        title="Analyze the image on the clipboard (or drop an image file here)"
+                        className="hover:bg-white/10 rounded-xl px-3"
+                    >
+                        <ClipboardPaste className="h-4 w-4" />
+                    </Button>
+                    <Button
+                        variant="ghost"
+                        onClick={toggleSpotlight}
+                        title={spotlight ? 'Stop dimming around highlighted targets' : 'Dim the screen around highlighted targets'}
+                        className={`hover:bg-white/10 rounded-xl px-3 ${spotlight ? 'text-blue-400' : ''}`}
+                    >
+                        <Focus className="h-4 w-4" />
+                    </Button>
+                    <Button
+                        variant="ghost"
+                        onClick={handleSelectRegion}
+                        disabled={isProcessing}
+                        title="Select a screen region to ask about"
+                        className="hover:bg-white/10 rounded-xl px-3"
+                    >
+                        <Crop className="h-4 w-4" />
+                    </Button>
                     <Button
                         onClick={handleSend}
                         disabled={!input.trim() || isProcessing}
@@ -0,0 +1,93 @@
+import { invoke } from '@tauri-apps/api/core'
+import type { CaptureResult, ChangeReport, EncodeOptions, RegionCapture } from '@/types/capture'
+import type { Point, BoundingBox } from '@/types/coordinates'
+import { SHAPES_VERSION, type Shape, type SpotlightOptions } from '@/types/shapes'
+import type { StyleOverrides } from '@/types/theme'
+
+// Captures stay in the Rust capture store; the webview shows them via capture.url
+// and only pulls the bytes when they have to be sent to the model.
+export async function captureToDataUrl(capture: CaptureResult): Promise<string> {
+  const buffer = await invoke<ArrayBuffer>('get_capture_bytes', { captureId: capture.captureId })
+  const bytes = new Uint8Array(buffer)
+
+  // Build the binary string in chunks to stay under argument count limits
+  let binary = ''
+  const chunkSize = 0x8000
+  for (let i = 0; i < bytes.length; i += chunkSize) {
+    binary += String.fromCharCode(...bytes.subarray(i, i + chunkSize))
+  }
+
+  return `data:image/${capture.encoding.format};base64,${btoa(binary)}`
+}
+
+// Whether the screen changed between two captures, judged on the Rust side
+export async function compareCaptures(beforeId: string, afterId: string): Promise<ChangeReport> {
+  return invoke<ChangeReport>('compare_captures', { beforeId, afterId })
+}
+
+// Let the user drag a rectangle on screen and capture it; null if they cancelled
+export async function selectRegion(encoding?: EncodeOptions, sessionId?: string): Promise<RegionCapture | null> {
+  return invoke<RegionCapture | null>('start_region_selection', { encoding, sessionId })
+}
+
+export interface Annotations {
+  points: Point[]
+  boxes: BoundingBox[]
+  walkthroughSteps?: number
+  currentStep?: number
+  instruction?: string
+  caption?: string
+  isComplete?: boolean
+  shapes?: Shape[]
+  spotlight?: SpotlightOptions
+  /** Per-export changes to the saved overlay theme */
+  style?: StyleOverrides
+}
+
+// PNG of a capture with the overlay's markers, boxes and step panel drawn in,
+// rendered on the Rust side for exporting to tickets and docs
+export async function renderAnnotatedCapture(captureId: string, annotations: Annotations): Promise<Blob> {
+  const buffer = await invoke<ArrayBuffer>('render_annotated_capture', {
+    captureId,
+    points: annotations.points,
+    boxes: annotations.boxes,
+    walkthroughSteps: annotations.walkthroughSteps,
+    currentStep: annotations.currentStep,
+    instruction: annotations.instruction,
+    caption: annotations.caption,
+    isComplete: annotations.isComplete,
+    shapes: annotations.shapes,
+    version: SHAPES_VERSION,
+    spotlight: annotations.spotlight,
+    style: annotations.style
+  })
+  return new Blob([buffer], { type: 'image/png' })
+}
+
+// Put an image that didn't come from the screen (customer screenshot, pasted
+// image) into the capture store so the usual flows can run on it
+export async function loadImageFile(path: string, encoding?: EncodeOptions, sessionId?: string): Promise<CaptureResult> {
+  return invoke<CaptureResult>('load_image_file', { path, encoding, sessionId })
+}
+
+export async function loadClipboardImage(encoding?: EncodeOptions, sessionId?: string): Promise<CaptureResult> {
+  return invoke<CaptureResult>('load_clipboard_image', { encoding, sessionId })
+}
+
+// Same rendering as renderAnnotatedCapture, placed on the system clipboard
+export async function copyAnnotatedCapture(captureId: string, annotations: Annotations): Promise<void> {
+  await invoke('copy_annotated_capture', {
+    captureId,
+    points: annotations.points,
+    boxes: annotations.boxes,
+    walkthroughSteps: annotations.walkthroughSteps,
+    currentStep: annotations.currentStep,
+    instruction: annotations.instruction,
+    caption: annotations.caption,
+    isComplete: annotations.isComplete,
+    shapes: annotations.shapes,
+    version: SHAPES_VERSION,
+    spotlight: annotations.spotlight,
+    style: annotations.style
+  })
+}
@@ -0,0 +1,28 @@
+import { invoke } from '@tauri-apps/api/core'
+import type { Point, BoundingBox } from '@/types/coordinates'
+import type { ClickListenerOptions, CursorStreamOptions } from '@/types/capture'
+
+export async function startCursorStream(options: CursorStreamOptions = {}): Promise<void> {
+  await invoke('start_cursor_stream', { options })
+}
+
+export async function stopCursorStream(): Promise<boolean> {
+  return invoke<boolean>('stop_cursor_stream')
+}
+
+// Report clicks on or off the targets of the step the overlay shows
+export async function startClickListener(options: ClickListenerOptions = {}): Promise<void> {
+  await invoke('start_click_listener', { options })
+}
+
+export async function stopClickListener(): Promise<boolean> {
+  return invoke<boolean>('stop_click_listener')
+}
+
+// Points the cursor should be guided to: each point and the center of each box
+export function cursorTargets(points: Point[], boxes: BoundingBox[]): Point[] {
+  return [
+    ...points,
+    ...boxes.map(b => ({ x: (b.xMin + b.xMax) / 2, y: (b.yMin + b.yMax) / 2 }))
+  ]
+}
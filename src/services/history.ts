@@ -0,0 +1,24 @@
+import { invoke } from '@tauri-apps/api/core'
+import type { HistoryEntry, RetentionPolicy } from '@/types/capture'
+
+// Every take_screenshot result is kept on disk until the retention policy sweeps it
+
+export async function listCaptures(sessionId?: string): Promise<HistoryEntry[]> {
+  return invoke<HistoryEntry[]>('list_captures', { sessionId })
+}
+
+export async function getCapture(captureId: string): Promise<HistoryEntry> {
+  return invoke<HistoryEntry>('get_capture', { captureId })
+}
+
+export async function deleteCaptures(options: { captureIds?: string[]; sessionId?: string }): Promise<number> {
+  return invoke<number>('delete_captures', options)
+}
+
+export async function getRetentionPolicy(): Promise<RetentionPolicy> {
+  return invoke<RetentionPolicy>('get_retention_policy')
+}
+
+export async function setRetentionPolicy(policy: RetentionPolicy): Promise<void> {
+  await invoke('set_retention_policy', { policy })
+}
@@ -0,0 +1,7 @@
+import { invoke } from '@tauri-apps/api/core'
+import type { OverlaySnapshot } from '@/types/overlay'
+
+// The backend owns what the overlays show; this reads it back, e.g. after a reload
+export async function getOverlayState(): Promise<OverlaySnapshot> {
+  return invoke<OverlaySnapshot>('get_overlay_state')
+}
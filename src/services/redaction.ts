@@ -0,0 +1,11 @@
+import { invoke } from '@tauri-apps/api/core'
+import type { RedactionRule } from '@/types/redaction'
+
+// Rules live in redactions.json in the app data dir and are applied by take_screenshot
+export async function getRedactionRules(): Promise<RedactionRule[]> {
+  return invoke<RedactionRule[]>('get_redaction_rules')
+}
+
+export async function setRedactionRules(rules: RedactionRule[]): Promise<void> {
+  await invoke('set_redaction_rules', { rules })
+}
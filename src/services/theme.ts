@@ -0,0 +1,11 @@
+import { invoke } from '@tauri-apps/api/core'
+import type { OverlayTheme } from '@/types/theme'
+
+// The theme is resolved into every overlay payload and annotated export, from the next step on
+export async function getOverlayTheme(): Promise<OverlayTheme> {
+  return invoke<OverlayTheme>('get_overlay_theme')
+}
+
+export async function setOverlayTheme(theme: OverlayTheme): Promise<void> {
+  await invoke('set_overlay_theme', { theme })
+}
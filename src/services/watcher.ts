@@ -0,0 +1,43 @@
+import { invoke } from '@tauri-apps/api/core'
+import type { Point, BoundingBox } from '@/types/coordinates'
+import type { CaptureResult, Rect, WatchOptions } from '@/types/capture'
+
+// Padding around a single point target, as a fraction of the capture
+const POINT_TARGET_PADDING = 0.04
+
+export async function startScreenWatcher(options: WatchOptions = {}): Promise<void> {
+  await invoke('start_screen_watcher', { options })
+}
+
+export async function stopScreenWatcher(): Promise<boolean> {
+  return invoke<boolean>('stop_screen_watcher')
+}
+
+// Desktop rectangle around a step's highlighted targets, or null when the step has none
+export function targetRegion(capture: CaptureResult, points: Point[], boxes: BoundingBox[]): Rect | null {
+  const normalized = [
+    ...boxes.map(b => ({ xMin: b.xMin, yMin: b.yMin, xMax: b.xMax, yMax: b.yMax })),
+    ...points.map(p => ({
+      xMin: p.x - POINT_TARGET_PADDING,
+      yMin: p.y - POINT_TARGET_PADDING,
+      xMax: p.x + POINT_TARGET_PADDING,
+      yMax: p.y + POINT_TARGET_PADDING
+    }))
+  ]
+  if (normalized.length === 0) return null
+
+  const clamp = (v: number) => Math.min(1, Math.max(0, v))
+  const xMin = clamp(Math.min(...normalized.map(b => b.xMin)))
+  const yMin = clamp(Math.min(...normalized.map(b => b.yMin)))
+  const xMax = clamp(Math.max(...normalized.map(b => b.xMax)))
+  const yMax = clamp(Math.max(...normalized.map(b => b.yMax)))
+  if (xMax <= xMin || yMax <= yMin) return null
+
+  const area = capture.cropLogical
+  return {
+    x: area.x + xMin * area.width,
+    y: area.y + yMin * area.height,
+    width: (xMax - xMin) * area.width,
+    height: (yMax - yMin) * area.height
+  }
+}
@@ -0,0 +1,214 @@
+import type { AppliedRedaction } from './redaction'
+import type { Point } from './coordinates'
+
+/**
+ * Capture types returned by the Rust screenshot commands.
+ */
+
+export interface ScreenInfo {
+  id: number
+  x: number
+  y: number
+  width: number
+  height: number
+  scaleFactor: number
+  isPrimary: boolean
+}
+
+/** A screen id, or "primary" / "focused" (the screen containing the focused window) */
+export type ScreenSelector = number | 'primary' | 'focused'
+
+/** PNG and WebP are lossless, so `quality` is rejected for them */
+export type OutputFormat = 'png' | 'jpeg' | 'webp'
+
+/** Encoding requested from take_screenshot; omitted fields use full-resolution PNG defaults */
+export interface EncodeOptions {
+  maxLongEdge?: number
+  format?: OutputFormat
+  /** 1-100, JPEG only (default 85); an error with PNG or WebP */
+  quality?: number
+  /** JPEG quality, then size, are reduced until the image fits */
+  maxBytes?: number
+}
+
+/** Parameters actually used to encode a capture */
+export interface EncodeReport {
+  format: OutputFormat
+  quality: number | null
+  width: number
+  height: number
+  bytes: number
+  attempts: number
+  withinBudget: boolean
+}
+
+/**
+ * How take_screenshot keeps Prism's windows out of the image: hide them while
+ * capturing (default), black out their rectangles, or leave them in
+ */
+export type ExclusionMode = 'hide' | 'mask' | 'none'
+
+export interface ExcludedWindow {
+  label: string
+  method: 'hidden' | 'masked'
+}
+
+/** Rectangle in logical desktop coordinates */
+export interface Rect {
+  x: number
+  y: number
+  width: number
+  height: number
+}
+
+/**
+ * A stored screenshot plus the metadata needed to map normalized Point/BoundingBox
+ * values back to the desktop: x = cropLogical.x + point.x * cropLogical.width
+ */
+export interface CaptureResult {
+  /** prism-capture:// URL of the stored image, usable as an <img> src */
+  url: string
+  captureId: string
+  /** Chat or walkthrough session the capture was taken for */
+  sessionId: string | null
+  screenId: number
+  /** Crop in physical pixels of the screen capture */
+  crop: Rect
+  /** Crop in logical desktop coordinates */
+  cropLogical: Rect
+  scaleFactor: number
+  /** Milliseconds since the Unix epoch */
+  timestamp: number
+  focusedWindow: FocusedWindowInfo | null
+  encoding: EncodeReport
+  excludedWindows: ExcludedWindow[]
+  /** Redaction rules applied before encoding */
+  redactions: AppliedRedaction[]
+  /** Pointer position normalized like Point, when includeCursor drew it into the image */
+  cursor: Point | null
+  /**
+   * 'window' when the focused window's own contents were read, unaffected by anything covering it;
+   * 'file' and 'clipboard' for imported images, which have no place on the desktop
+   */
+  source: 'screen' | 'window' | 'file' | 'clipboard'
+  /** Why window capture was not possible, when the capture fell back to screen pixels */
+  windowCaptureError: string | null
+}
+
+export interface FocusedWindowInfo {
+  owner_name: string
+  window_name: string
+  window_id: number
+  process_id: number
+  /** X11 window or CGWindowID, when known */
+  native_id?: number
+  x: number
+  y: number
+  width: number
+  height: number
+}
+
+export interface ChangedRegion {
+  /** Normalized 0-1 to the newer capture */
+  bounds: Rect
+  /** Same region in logical desktop coordinates */
+  desktop: Rect
+  cells: number
+}
+
+/** Result of compare_captures */
+export interface ChangeReport {
+  changed: boolean
+  /** Fraction of grid cells that changed, 0-1 */
+  score: number
+  /** Hamming distance between perceptual hashes, 0-64 */
+  hashDistance: number
+  regions: ChangedRegion[]
+}
+
+/** Options for start_screen_watcher; all fields optional */
+export interface WatchOptions {
+  intervalMs?: number
+  /** Logical desktop rectangle to watch; defaults to the focused window */
+  region?: Rect
+  screen?: ScreenSelector
+  /** Mean brightness change (0-255) a grid cell needs to count as changed */
+  threshold?: number
+  /** Long edge in pixels of the sampled frames */
+  sampleSize?: number
+}
+
+/** Payload of the screen-changed event */
+export interface ScreenChange {
+  score: number
+  hashDistance: number
+  /** Bounding box of all changes, normalized 0-1 to `area` */
+  bounds: Rect
+  /** Same box in logical desktop coordinates */
+  desktop: Rect
+  regions: ChangedRegion[]
+  /** Logical desktop rectangle that was watched */
+  area: Rect
+  timestamp: number
+}
+
+/** Result of start_region_selection */
+export interface RegionCapture {
+  /** Selected rectangle in logical desktop coordinates */
+  rect: Rect
+  /** Capture of just that rectangle */
+  capture: CaptureResult
+}
+
+/** Sidecar record of a capture in the on-disk history */
+export interface HistoryEntry extends CaptureResult {
+  mimeType: string
+  /** Image file name inside the history directory */
+  file: string
+  bytes: number
+}
+
+/** How long captures are kept on disk; null disables a limit */
+export interface RetentionPolicy {
+  maxAgeHours: number | null
+  maxTotalBytes: number | null
+}
+
+/** Options for start_cursor_stream; all fields optional */
+export interface CursorStreamOptions {
+  /** Minimum time between cursor-moved events */
+  intervalMs?: number
+  /** Capture whose area defines the normalized space; defaults to the current capture area */
+  captureId?: string
+  /** Normalized targets to measure the distance to; defaults to the points and box centers of the step the overlay shows */
+  targets?: Point[]
+}
+
+/** Payload of the cursor-moved event */
+export interface CursorMoved {
+  /** Normalized like Point; outside 0-1 when the pointer is off the area */
+  position: Point
+  desktopX: number
+  desktopY: number
+  inside: boolean
+  /** Nearest target and its distance in logical pixels */
+  target: Point | null
+  distance: number | null
+}
+
+export interface ClickListenerOptions {
+  /** How often the mouse button is polled */
+  intervalMs?: number
+  /** Logical pixels around a target that still count as a hit */
+  tolerance?: number
+}
+
+/** Payload of the target-clicked and off-target-click events */
+export interface TargetClick {
+  desktopX: number
+  desktopY: number
+  /** Index of the hit (or nearest) target among the step's points, boxes and shapes */
+  target: number | null
+  distance: number | null
+  onTarget: boolean
+}
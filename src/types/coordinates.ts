@@ -4,9 +4,16 @@
  * converted to Rust snake_case via serde(rename_all = "camelCase").
  */
 
+/**
+ * Overlay items are normalized 0-1 to the capture area unless they name a
+ * display (`screenId`, normalized to that screen) or set `desktop` (logical
+ * desktop pixels). The overlay routes each item to the display it lands on.
+ */
 export interface Point {
   x: number
   y: number
+  screenId?: number
+  desktop?: boolean
 }
 
 export interface BoundingBox {
@@ -14,4 +21,6 @@ export interface BoundingBox {
   yMin: number
   xMax: number
   yMax: number
+  screenId?: number
+  desktop?: boolean
 }
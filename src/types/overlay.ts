@@ -0,0 +1,78 @@
+import type { Point, BoundingBox } from './coordinates'
+import type { Rect } from './capture'
+import type { Magnifier, Shape, Spotlight } from './shapes'
+import type { OverlayStyle } from './theme'
+
+/** Per-step options for getting the overlay out of the way (Cmd/Ctrl+Shift+H peeks at any time) */
+export interface VisibilityOptions {
+  /** Fade the overlay out this many seconds after the step is shown (at most 3600) */
+  autoHideSecs?: number
+  /** Bring a hidden overlay back when the screen watcher sees a change; needs a running start_screen_watcher */
+  reappearOnChange?: boolean
+}
+
+/** Payload of overlay-visibility, sent to each overlay when it fades in or out */
+export interface OverlayVisibility {
+  visible: boolean
+}
+
+/** Line from a label that had to move away from its target back to it */
+export interface LabelLeader {
+  x1: number
+  y1: number
+  x2: number
+  y2: number
+}
+
+/**
+ * Position of a caption (points and boxes) or shape label, laid out by the backend
+ * so labels stay on screen and clear of each other. Logical pixels of the overlay.
+ */
+export interface OverlayLabel {
+  target: 'point' | 'box' | 'shape'
+  /** Position of the item in the payload's points, boxes or shapes */
+  index: number
+  text: string
+  x: number
+  y: number
+  width: number
+  height: number
+  leader?: LabelLeader
+}
+
+/** One overlay's part of a step, as sent on overlay-data */
+export interface OverlayData {
+  points: Point[]
+  boxes: BoundingBox[]
+  /** Shape protocol version the payload was written for */
+  version?: number
+  shapes?: Shape[]
+  walkthroughSteps?: number
+  currentStep?: number
+  instruction?: string
+  caption?: string
+  isComplete?: boolean
+  /** Resolved theme: saved preset and overrides, then this step's */
+  style: OverlayStyle
+  /** Dim mask for this step, with holes already cut by the backend */
+  spotlight?: Spotlight
+  /** Zoomed inset of the first target; only the display with that target gets it */
+  magnifier?: Magnifier
+  labels?: OverlayLabel[]
+  /** Desktop bounds of the display this overlay covers */
+  screenBounds?: Rect
+  /** Capture area that cursor targets are normalized to */
+  area?: Rect
+  /** Whether this overlay shows the step panel (one display per step) */
+  isHome?: boolean
+}
+
+/** Returned by get_overlay_state: the step as last sent, before it is split per display */
+export interface OverlaySnapshot {
+  area: Rect | null
+  payload: OverlayData | null
+  /** Peeked away or auto-hidden; the step is kept */
+  hidden: boolean
+  /** Displays that have an overlay window right now */
+  openScreens: number[]
+}
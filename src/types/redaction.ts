@@ -0,0 +1,29 @@
+import type { Rect } from './capture'
+
+export type RedactionStyle = 'blackout' | 'blur'
+
+/** What a redaction rule covers */
+export type RedactionTarget =
+  /** Fixed rectangle in logical desktop coordinates */
+  | { kind: 'rect'; rect: Rect }
+  /** Every window of an app, matched by process name (macOS only) */
+  | { kind: 'app'; appName: string }
+  /** User-drawn region, normalized 0-1 to the capture area */
+  | { kind: 'region'; bounds: Rect }
+
+export interface RedactionRule {
+  id: string
+  label?: string | null
+  enabled: boolean
+  style: RedactionStyle
+  target: RedactionTarget
+}
+
+/** A redaction applied to a capture, returned with it for auditing */
+export interface AppliedRedaction {
+  ruleId: string
+  label: string | null
+  style: RedactionStyle
+  /** Redacted area in logical desktop coordinates */
+  area: Rect
+}
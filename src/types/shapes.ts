@@ -0,0 +1,88 @@
+import type { Point, BoundingBox } from './coordinates'
+import type { Rect } from './capture'
+
+/** Shape protocol version understood by this build (mirrors shapes::PROTOCOL_VERSION) */
+export const SHAPES_VERSION = 1
+
+/** Rendering hints; the overlay picks defaults per shape type */
+export interface ShapeStyle {
+  /** #rgb or #rrggbb */
+  color?: string
+  /** Logical pixels */
+  strokeWidth?: number
+  dashed?: boolean
+  /** 0-1, for boxes and polygons */
+  fillOpacity?: number
+}
+
+interface ShapeBase {
+  label?: string
+  style?: ShapeStyle
+}
+
+/**
+ * Overlay shapes, tagged by `type`. Coordinates follow the same rules as
+ * Point and BoundingBox; the backend validates them before drawing.
+ */
+export type Shape = ShapeBase & (
+  | { type: 'point'; at: Point }
+  | { type: 'box'; bounds: BoundingBox }
+  | { type: 'arrow'; from: Point; to: Point }
+  | { type: 'polyline'; points: Point[] }
+  | { type: 'polygon'; points: Point[] }
+  | { type: 'text'; at: Point; text: string }
+  | { type: 'badge'; at: Point; number: number }
+)
+
+/**
+ * Spotlight mode: the overlay dims everything except the step's targets.
+ * Send it with a step to turn the mask on for that step; fields default on the Rust side.
+ */
+export interface SpotlightOptions {
+  /** Logical pixels of room around boxes and shapes */
+  padding?: number
+  cornerRadius?: number
+  /** Radius of the round hole around points */
+  pointRadius?: number
+  /** Mask darkness, 0-1 */
+  opacity?: number
+}
+
+/** A hole in the spotlight mask, in logical pixels of the overlay */
+export interface Cutout {
+  x: number
+  y: number
+  width: number
+  height: number
+  radius: number
+}
+
+/** Spotlight as delivered to an overlay, with holes computed by the backend */
+export interface Spotlight extends Required<SpotlightOptions> {
+  cutouts: Cutout[]
+}
+
+/**
+ * Magnifier inset: a zoomed callout of the region around the step's first target,
+ * cut from the latest capture. Send it with a step to turn it on; fields default on the Rust side.
+ */
+export interface MagnifierOptions {
+  zoom?: number
+  /** Logical pixels around the target's center that go into the inset */
+  radius?: number
+  /** Targets larger than this (logical pixels) are not magnified */
+  maxTarget?: number
+  /** Logical pixels between the magnified region and the callout */
+  gap?: number
+}
+
+/**
+ * Magnifier as delivered to an overlay. Rects are in logical pixels of the overlay;
+ * `image` and the rects are missing when there was nothing small enough to magnify.
+ */
+export interface Magnifier extends Required<MagnifierOptions> {
+  /** PNG data URL of the zoomed region */
+  image?: string
+  source?: Rect
+  callout?: Rect
+}
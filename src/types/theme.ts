@@ -0,0 +1,26 @@
+/** Built-in overlay looks; the chosen one is saved by the backend */
+export type StylePreset = 'standard' | 'colorBlindSafe' | 'highContrast' | 'minimal'
+
+/** How the overlay and annotated exports draw markers, boxes and labels */
+export interface OverlayStyle {
+  /** Points, arrows, lines and badges (#rgb or #rrggbb) */
+  pointColor: string
+  /** Boxes and polygons */
+  boxColor: string
+  labelTextColor: string
+  /** Logical pixels */
+  strokeWidth: number
+  /** 0-1, for boxes and polygons */
+  fillOpacity: number
+  /** Pulse and ping animations on the current step */
+  animate: boolean
+}
+
+/** Changes on top of a preset: saved with the theme, or sent with one step */
+export type StyleOverrides = Partial<OverlayStyle>
+
+/** Persisted in overlay-theme.json in the app data dir */
+export interface OverlayTheme {
+  preset: StylePreset
+  overrides: StyleOverrides
+}
@@ -1,5 +1,6 @@
 // Shared type definitions for walkthrough functionality
 import type { Point, BoundingBox } from './coordinates'
+import type { StyleOverrides } from './theme'
 
 // Re-export coordinate types for convenience
 export type { Point, BoundingBox }
@@ -36,11 +37,14 @@ export interface WalkthroughStepResult {
 
 export interface WalkthroughStep {
   stepNumber: number
+  /** Capture URL (prism-capture://) of the screenshot this step was based on */
   screenshot: string
   caption: string
   instruction: string
   points: Point[]
   boxes: BoundingBox[]
+  /** Changes to the saved overlay theme for this step only */
+  style?: StyleOverrides
 }
 
 export interface WalkthroughSession {
@@ -55,6 +59,7 @@ export interface Message {
   id: string
   role: 'user' | 'assistant'
   content: string
+  /** Capture URL (prism-capture://) */
   image?: string
   points?: Point[]
   boxes?: BoundingBox[]